@@ -0,0 +1,2 @@
+DROP INDEX IF EXISTS idx_transactions_matched_package;
+ALTER TABLE transactions DROP COLUMN IF EXISTS matched_package;
@@ -0,0 +1,4 @@
+-- Record which tracked package a transaction matched on
+ALTER TABLE transactions ADD COLUMN matched_package VARCHAR;
+
+CREATE INDEX idx_transactions_matched_package ON transactions(matched_package);
@@ -0,0 +1,12 @@
+-- Known versions of tracked packages, so upgrades are followed across restarts
+CREATE TABLE package_lineage (
+    package_id VARCHAR PRIMARY KEY,
+    original_package_id VARCHAR NOT NULL,
+    previous_package_id VARCHAR NOT NULL,
+    version BIGINT NOT NULL,
+    tx_digest VARCHAR NOT NULL,
+    checkpoint_sequence_number BIGINT NOT NULL,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW()
+);
+
+CREATE INDEX idx_package_lineage_original ON package_lineage(original_package_id);
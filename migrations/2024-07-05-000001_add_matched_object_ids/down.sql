@@ -0,0 +1,2 @@
+DROP INDEX IF EXISTS idx_transactions_matched_object_ids;
+ALTER TABLE transactions DROP COLUMN IF EXISTS matched_object_ids;
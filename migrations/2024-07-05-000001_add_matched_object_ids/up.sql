@@ -0,0 +1,4 @@
+-- Record the objects that matched the object-type filter
+ALTER TABLE transactions ADD COLUMN matched_object_ids TEXT[];
+
+CREATE INDEX idx_transactions_matched_object_ids ON transactions USING GIN (matched_object_ids);
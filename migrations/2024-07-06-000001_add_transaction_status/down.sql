@@ -0,0 +1,3 @@
+DROP INDEX IF EXISTS idx_transactions_status;
+ALTER TABLE transactions DROP COLUMN IF EXISTS error;
+ALTER TABLE transactions DROP COLUMN IF EXISTS status;
@@ -0,0 +1,5 @@
+-- Record execution status; rows indexed before this migration are 'unknown'
+ALTER TABLE transactions ADD COLUMN status VARCHAR NOT NULL DEFAULT 'unknown';
+ALTER TABLE transactions ADD COLUMN error VARCHAR;
+
+CREATE INDEX idx_transactions_status ON transactions(status);
@@ -0,0 +1,17 @@
+-- One row per MoveCall command in a matched transaction
+CREATE TABLE move_calls (
+    tx_digest VARCHAR NOT NULL REFERENCES transactions(tx_digest) ON DELETE CASCADE,
+    command_index INT NOT NULL,
+    package VARCHAR NOT NULL,
+    module VARCHAR NOT NULL,
+    function VARCHAR NOT NULL,
+    type_arguments JSONB NOT NULL,
+    arguments JSONB NOT NULL,
+    checkpoint_sequence_number BIGINT NOT NULL,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+    PRIMARY KEY (tx_digest, command_index)
+);
+
+CREATE INDEX idx_move_calls_package ON move_calls(package);
+CREATE INDEX idx_move_calls_function ON move_calls(package, module, function);
+CREATE INDEX idx_move_calls_checkpoint ON move_calls(checkpoint_sequence_number);
@@ -0,0 +1,18 @@
+-- One row per event emitted by a matched transaction
+CREATE TABLE events (
+    tx_digest VARCHAR NOT NULL REFERENCES transactions(tx_digest) ON DELETE CASCADE,
+    event_seq BIGINT NOT NULL,
+    package_id VARCHAR NOT NULL,
+    module VARCHAR NOT NULL,
+    event_type VARCHAR NOT NULL,
+    sender VARCHAR NOT NULL,
+    contents BYTEA NOT NULL,
+    checkpoint_sequence_number BIGINT NOT NULL,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+    PRIMARY KEY (tx_digest, event_seq)
+);
+
+CREATE INDEX idx_events_event_type ON events(event_type);
+CREATE INDEX idx_events_package_module ON events(package_id, module);
+CREATE INDEX idx_events_sender ON events(sender);
+CREATE INDEX idx_events_checkpoint ON events(checkpoint_sequence_number);
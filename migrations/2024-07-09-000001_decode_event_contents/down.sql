@@ -0,0 +1,3 @@
+DROP TABLE IF EXISTS packages;
+DROP INDEX IF EXISTS idx_events_parsed_json;
+ALTER TABLE events DROP COLUMN IF EXISTS parsed_json;
@@ -0,0 +1,16 @@
+-- Event contents decoded with the Move layout of the event type
+ALTER TABLE events ADD COLUMN parsed_json JSONB;
+
+CREATE INDEX idx_events_parsed_json ON events USING GIN (parsed_json);
+
+-- Packages seen by the indexer, kept so Move layouts survive restarts
+CREATE TABLE packages (
+    package_id VARCHAR PRIMARY KEY,
+    original_package_id VARCHAR NOT NULL,
+    version BIGINT NOT NULL,
+    move_package BYTEA NOT NULL,
+    checkpoint_sequence_number BIGINT NOT NULL,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW()
+);
+
+CREATE INDEX idx_packages_original ON packages(original_package_id);
@@ -0,0 +1,19 @@
+-- One row per input and output object of a matched transaction, with the
+-- Move contents decoded using the object type's layout
+CREATE TABLE transaction_objects (
+    tx_digest VARCHAR NOT NULL REFERENCES transactions(tx_digest) ON DELETE CASCADE,
+    object_id VARCHAR NOT NULL,
+    version BIGINT NOT NULL,
+    kind VARCHAR NOT NULL,
+    object_type VARCHAR,
+    owner_kind VARCHAR NOT NULL,
+    owner_address VARCHAR,
+    parsed_json JSONB,
+    checkpoint_sequence_number BIGINT NOT NULL,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+    PRIMARY KEY (tx_digest, object_id, version)
+);
+
+CREATE INDEX idx_transaction_objects_object ON transaction_objects(object_id, version);
+CREATE INDEX idx_transaction_objects_type ON transaction_objects(object_type);
+CREATE INDEX idx_transaction_objects_owner ON transaction_objects(owner_address);
@@ -0,0 +1,36 @@
+-- Every version of every object written or removed by a matched transaction
+CREATE TABLE objects_history (
+    object_id VARCHAR NOT NULL,
+    version BIGINT NOT NULL,
+    digest VARCHAR NOT NULL,
+    object_type VARCHAR,
+    owner_kind VARCHAR,
+    owner_address VARCHAR,
+    tx_digest VARCHAR NOT NULL,
+    checkpoint_sequence_number BIGINT NOT NULL,
+    deleted BOOLEAN NOT NULL,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+    PRIMARY KEY (object_id, version)
+);
+
+CREATE INDEX idx_objects_history_tx_digest ON objects_history(tx_digest);
+CREATE INDEX idx_objects_history_checkpoint ON objects_history(checkpoint_sequence_number);
+CREATE INDEX idx_objects_history_owner ON objects_history(owner_address);
+
+-- Latest known version of each tracked object; removed objects are kept with
+-- deleted = true so that older versions committed later cannot resurrect them
+CREATE TABLE objects (
+    object_id VARCHAR PRIMARY KEY,
+    version BIGINT NOT NULL,
+    digest VARCHAR NOT NULL,
+    object_type VARCHAR,
+    owner_kind VARCHAR,
+    owner_address VARCHAR,
+    tx_digest VARCHAR NOT NULL,
+    checkpoint_sequence_number BIGINT NOT NULL,
+    deleted BOOLEAN NOT NULL,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW()
+);
+
+CREATE INDEX idx_objects_type ON objects(object_type);
+CREATE INDEX idx_objects_owner ON objects(owner_address);
@@ -0,0 +1,13 @@
+-- Net coin balance change per owner and coin type for each matched transaction
+CREATE TABLE balance_changes (
+    tx_digest VARCHAR NOT NULL REFERENCES transactions(tx_digest) ON DELETE CASCADE,
+    owner VARCHAR NOT NULL,
+    coin_type VARCHAR NOT NULL,
+    amount NUMERIC NOT NULL,
+    checkpoint_sequence_number BIGINT NOT NULL,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+    PRIMARY KEY (tx_digest, owner, coin_type)
+);
+
+CREATE INDEX idx_balance_changes_owner ON balance_changes(owner, coin_type);
+CREATE INDEX idx_balance_changes_coin_type ON balance_changes(coin_type);
@@ -0,0 +1,6 @@
+DROP INDEX IF EXISTS idx_transactions_gas_owner;
+ALTER TABLE transactions DROP COLUMN IF EXISTS non_refundable_storage_fee;
+ALTER TABLE transactions DROP COLUMN IF EXISTS storage_rebate;
+ALTER TABLE transactions DROP COLUMN IF EXISTS storage_cost;
+ALTER TABLE transactions DROP COLUMN IF EXISTS computation_cost;
+ALTER TABLE transactions DROP COLUMN IF EXISTS gas_owner;
@@ -0,0 +1,12 @@
+-- Gas actually spent, from the effects' gas cost summary, and who paid for it.
+-- Rows indexed before this migration are backfilled with the sender and zero costs.
+ALTER TABLE transactions ADD COLUMN gas_owner VARCHAR;
+UPDATE transactions SET gas_owner = sender;
+ALTER TABLE transactions ALTER COLUMN gas_owner SET NOT NULL;
+
+ALTER TABLE transactions ADD COLUMN computation_cost BIGINT NOT NULL DEFAULT 0;
+ALTER TABLE transactions ADD COLUMN storage_cost BIGINT NOT NULL DEFAULT 0;
+ALTER TABLE transactions ADD COLUMN storage_rebate BIGINT NOT NULL DEFAULT 0;
+ALTER TABLE transactions ADD COLUMN non_refundable_storage_fee BIGINT NOT NULL DEFAULT 0;
+
+CREATE INDEX idx_transactions_gas_owner ON transactions(gas_owner);
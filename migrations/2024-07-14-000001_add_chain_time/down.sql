@@ -0,0 +1,37 @@
+DROP INDEX IF EXISTS idx_transactions_epoch;
+DROP INDEX IF EXISTS idx_output_objects_timestamp;
+ALTER TABLE output_objects DROP COLUMN IF EXISTS epoch;
+ALTER TABLE output_objects DROP COLUMN IF EXISTS timestamp_ms;
+DROP INDEX IF EXISTS idx_input_objects_timestamp;
+ALTER TABLE input_objects DROP COLUMN IF EXISTS epoch;
+ALTER TABLE input_objects DROP COLUMN IF EXISTS timestamp_ms;
+DROP INDEX IF EXISTS idx_transaction_events_timestamp;
+ALTER TABLE transaction_events DROP COLUMN IF EXISTS epoch;
+ALTER TABLE transaction_events DROP COLUMN IF EXISTS timestamp_ms;
+DROP INDEX IF EXISTS idx_transaction_effects_timestamp;
+ALTER TABLE transaction_effects DROP COLUMN IF EXISTS epoch;
+ALTER TABLE transaction_effects DROP COLUMN IF EXISTS timestamp_ms;
+DROP INDEX IF EXISTS idx_checkpoint_transactions_timestamp;
+ALTER TABLE checkpoint_transactions DROP COLUMN IF EXISTS epoch;
+ALTER TABLE checkpoint_transactions DROP COLUMN IF EXISTS timestamp_ms;
+DROP INDEX IF EXISTS idx_balance_changes_timestamp;
+ALTER TABLE balance_changes DROP COLUMN IF EXISTS epoch;
+ALTER TABLE balance_changes DROP COLUMN IF EXISTS timestamp_ms;
+DROP INDEX IF EXISTS idx_objects_timestamp;
+ALTER TABLE objects DROP COLUMN IF EXISTS epoch;
+ALTER TABLE objects DROP COLUMN IF EXISTS timestamp_ms;
+DROP INDEX IF EXISTS idx_objects_history_timestamp;
+ALTER TABLE objects_history DROP COLUMN IF EXISTS epoch;
+ALTER TABLE objects_history DROP COLUMN IF EXISTS timestamp_ms;
+DROP INDEX IF EXISTS idx_transaction_objects_timestamp;
+ALTER TABLE transaction_objects DROP COLUMN IF EXISTS epoch;
+ALTER TABLE transaction_objects DROP COLUMN IF EXISTS timestamp_ms;
+DROP INDEX IF EXISTS idx_events_timestamp;
+ALTER TABLE events DROP COLUMN IF EXISTS epoch;
+ALTER TABLE events DROP COLUMN IF EXISTS timestamp_ms;
+DROP INDEX IF EXISTS idx_move_calls_timestamp;
+ALTER TABLE move_calls DROP COLUMN IF EXISTS epoch;
+ALTER TABLE move_calls DROP COLUMN IF EXISTS timestamp_ms;
+DROP INDEX IF EXISTS idx_transactions_timestamp;
+ALTER TABLE transactions DROP COLUMN IF EXISTS epoch;
+ALTER TABLE transactions DROP COLUMN IF EXISTS timestamp_ms;
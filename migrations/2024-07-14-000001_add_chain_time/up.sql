@@ -0,0 +1,52 @@
+-- Chain time of the checkpoint each row came from. Rows indexed before this
+-- migration have 0 for both columns.
+
+ALTER TABLE transactions ADD COLUMN timestamp_ms BIGINT NOT NULL DEFAULT 0;
+ALTER TABLE transactions ADD COLUMN epoch BIGINT NOT NULL DEFAULT 0;
+CREATE INDEX idx_transactions_timestamp ON transactions(timestamp_ms);
+
+ALTER TABLE move_calls ADD COLUMN timestamp_ms BIGINT NOT NULL DEFAULT 0;
+ALTER TABLE move_calls ADD COLUMN epoch BIGINT NOT NULL DEFAULT 0;
+CREATE INDEX idx_move_calls_timestamp ON move_calls(timestamp_ms);
+
+ALTER TABLE events ADD COLUMN timestamp_ms BIGINT NOT NULL DEFAULT 0;
+ALTER TABLE events ADD COLUMN epoch BIGINT NOT NULL DEFAULT 0;
+CREATE INDEX idx_events_timestamp ON events(timestamp_ms);
+
+ALTER TABLE transaction_objects ADD COLUMN timestamp_ms BIGINT NOT NULL DEFAULT 0;
+ALTER TABLE transaction_objects ADD COLUMN epoch BIGINT NOT NULL DEFAULT 0;
+CREATE INDEX idx_transaction_objects_timestamp ON transaction_objects(timestamp_ms);
+
+ALTER TABLE objects_history ADD COLUMN timestamp_ms BIGINT NOT NULL DEFAULT 0;
+ALTER TABLE objects_history ADD COLUMN epoch BIGINT NOT NULL DEFAULT 0;
+CREATE INDEX idx_objects_history_timestamp ON objects_history(timestamp_ms);
+
+ALTER TABLE objects ADD COLUMN timestamp_ms BIGINT NOT NULL DEFAULT 0;
+ALTER TABLE objects ADD COLUMN epoch BIGINT NOT NULL DEFAULT 0;
+CREATE INDEX idx_objects_timestamp ON objects(timestamp_ms);
+
+ALTER TABLE balance_changes ADD COLUMN timestamp_ms BIGINT NOT NULL DEFAULT 0;
+ALTER TABLE balance_changes ADD COLUMN epoch BIGINT NOT NULL DEFAULT 0;
+CREATE INDEX idx_balance_changes_timestamp ON balance_changes(timestamp_ms);
+
+ALTER TABLE checkpoint_transactions ADD COLUMN timestamp_ms BIGINT NOT NULL DEFAULT 0;
+ALTER TABLE checkpoint_transactions ADD COLUMN epoch BIGINT NOT NULL DEFAULT 0;
+CREATE INDEX idx_checkpoint_transactions_timestamp ON checkpoint_transactions(timestamp_ms);
+
+ALTER TABLE transaction_effects ADD COLUMN timestamp_ms BIGINT NOT NULL DEFAULT 0;
+ALTER TABLE transaction_effects ADD COLUMN epoch BIGINT NOT NULL DEFAULT 0;
+CREATE INDEX idx_transaction_effects_timestamp ON transaction_effects(timestamp_ms);
+
+ALTER TABLE transaction_events ADD COLUMN timestamp_ms BIGINT NOT NULL DEFAULT 0;
+ALTER TABLE transaction_events ADD COLUMN epoch BIGINT NOT NULL DEFAULT 0;
+CREATE INDEX idx_transaction_events_timestamp ON transaction_events(timestamp_ms);
+
+ALTER TABLE input_objects ADD COLUMN timestamp_ms BIGINT NOT NULL DEFAULT 0;
+ALTER TABLE input_objects ADD COLUMN epoch BIGINT NOT NULL DEFAULT 0;
+CREATE INDEX idx_input_objects_timestamp ON input_objects(timestamp_ms);
+
+ALTER TABLE output_objects ADD COLUMN timestamp_ms BIGINT NOT NULL DEFAULT 0;
+ALTER TABLE output_objects ADD COLUMN epoch BIGINT NOT NULL DEFAULT 0;
+CREATE INDEX idx_output_objects_timestamp ON output_objects(timestamp_ms);
+
+CREATE INDEX idx_transactions_epoch ON transactions(epoch);
@@ -0,0 +1,19 @@
+-- Every checkpoint processed by the indexer, whether or not it had matches.
+-- Gas columns hold the epoch's rolling gas cost summary as of the checkpoint.
+CREATE TABLE checkpoints (
+    sequence_number BIGINT PRIMARY KEY,
+    digest VARCHAR NOT NULL,
+    epoch BIGINT NOT NULL,
+    timestamp_ms BIGINT NOT NULL,
+    network_total_transactions BIGINT NOT NULL,
+    transaction_count BIGINT NOT NULL,
+    matched_transactions BIGINT NOT NULL,
+    computation_cost BIGINT NOT NULL,
+    storage_cost BIGINT NOT NULL,
+    storage_rebate BIGINT NOT NULL,
+    non_refundable_storage_fee BIGINT NOT NULL,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW()
+);
+
+CREATE INDEX idx_checkpoints_epoch ON checkpoints(epoch);
+CREATE INDEX idx_checkpoints_timestamp ON checkpoints(timestamp_ms);
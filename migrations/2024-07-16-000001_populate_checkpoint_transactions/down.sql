@@ -0,0 +1,7 @@
+DROP INDEX IF EXISTS idx_checkpoint_transactions_effects_digest;
+DROP INDEX IF EXISTS idx_checkpoint_transactions_position;
+ALTER TABLE checkpoint_transactions DROP COLUMN IF EXISTS tx_sequence_in_checkpoint;
+ALTER TABLE checkpoint_transactions DROP COLUMN IF EXISTS checkpoint_sequence_number;
+ALTER TABLE checkpoint_transactions ALTER COLUMN transaction_effects_digest DROP NOT NULL;
+ALTER TABLE checkpoint_transactions ADD COLUMN input_objects_digest VARCHAR;
+ALTER TABLE checkpoint_transactions ADD COLUMN output_objects_digest VARCHAR;
@@ -0,0 +1,16 @@
+-- checkpoint_transactions was never written to, so it can be reshaped in place:
+-- input/output object digests have no on-chain counterpart and are dropped,
+-- the effects digest is always known, and each row records its checkpoint
+-- and position within it.
+DELETE FROM checkpoint_transactions;
+
+ALTER TABLE checkpoint_transactions DROP COLUMN input_objects_digest;
+ALTER TABLE checkpoint_transactions DROP COLUMN output_objects_digest;
+ALTER TABLE checkpoint_transactions ALTER COLUMN transaction_effects_digest SET NOT NULL;
+ALTER TABLE checkpoint_transactions ADD COLUMN checkpoint_sequence_number BIGINT NOT NULL;
+ALTER TABLE checkpoint_transactions ADD COLUMN tx_sequence_in_checkpoint BIGINT NOT NULL;
+
+CREATE UNIQUE INDEX idx_checkpoint_transactions_position
+    ON checkpoint_transactions(checkpoint_sequence_number, tx_sequence_in_checkpoint);
+CREATE INDEX idx_checkpoint_transactions_effects_digest
+    ON checkpoint_transactions(transaction_effects_digest);
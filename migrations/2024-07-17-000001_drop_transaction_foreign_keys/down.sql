@@ -0,0 +1,20 @@
+ALTER TABLE balance_changes ADD CONSTRAINT balance_changes_tx_digest_fkey
+    FOREIGN KEY (tx_digest) REFERENCES transactions(tx_digest) ON DELETE CASCADE;
+ALTER TABLE transaction_objects ADD CONSTRAINT transaction_objects_tx_digest_fkey
+    FOREIGN KEY (tx_digest) REFERENCES transactions(tx_digest) ON DELETE CASCADE;
+ALTER TABLE events ADD CONSTRAINT events_tx_digest_fkey
+    FOREIGN KEY (tx_digest) REFERENCES transactions(tx_digest) ON DELETE CASCADE;
+ALTER TABLE move_calls ADD CONSTRAINT move_calls_tx_digest_fkey
+    FOREIGN KEY (tx_digest) REFERENCES transactions(tx_digest) ON DELETE CASCADE;
+ALTER TABLE output_objects ADD CONSTRAINT output_objects_tx_digest_fkey
+    FOREIGN KEY (tx_digest) REFERENCES transactions(tx_digest) ON DELETE CASCADE;
+ALTER TABLE input_objects ADD CONSTRAINT input_objects_tx_digest_fkey
+    FOREIGN KEY (tx_digest) REFERENCES transactions(tx_digest) ON DELETE CASCADE;
+ALTER TABLE transaction_events ADD CONSTRAINT transaction_events_tx_digest_fkey
+    FOREIGN KEY (tx_digest) REFERENCES transactions(tx_digest) ON DELETE CASCADE;
+ALTER TABLE transaction_effects ADD CONSTRAINT transaction_effects_tx_digest_fkey
+    FOREIGN KEY (tx_digest) REFERENCES transactions(tx_digest) ON DELETE CASCADE;
+ALTER TABLE checkpoint_transactions ADD CONSTRAINT checkpoint_transactions_transaction_digest_fkey
+    FOREIGN KEY (transaction_digest) REFERENCES transactions(tx_digest) ON DELETE CASCADE;
+ALTER TABLE checkpoint_transactions ADD CONSTRAINT checkpoint_transactions_tx_digest_fkey
+    FOREIGN KEY (tx_digest) REFERENCES transactions(tx_digest) ON DELETE CASCADE;
@@ -0,0 +1,13 @@
+-- Each table is now written by its own pipeline, so rows derived from a
+-- transaction can be committed before the transaction itself. Keep tx_digest
+-- as a plain column instead of a foreign key to transactions.
+ALTER TABLE checkpoint_transactions DROP CONSTRAINT IF EXISTS checkpoint_transactions_tx_digest_fkey;
+ALTER TABLE checkpoint_transactions DROP CONSTRAINT IF EXISTS checkpoint_transactions_transaction_digest_fkey;
+ALTER TABLE transaction_effects DROP CONSTRAINT IF EXISTS transaction_effects_tx_digest_fkey;
+ALTER TABLE transaction_events DROP CONSTRAINT IF EXISTS transaction_events_tx_digest_fkey;
+ALTER TABLE input_objects DROP CONSTRAINT IF EXISTS input_objects_tx_digest_fkey;
+ALTER TABLE output_objects DROP CONSTRAINT IF EXISTS output_objects_tx_digest_fkey;
+ALTER TABLE move_calls DROP CONSTRAINT IF EXISTS move_calls_tx_digest_fkey;
+ALTER TABLE events DROP CONSTRAINT IF EXISTS events_tx_digest_fkey;
+ALTER TABLE transaction_objects DROP CONSTRAINT IF EXISTS transaction_objects_tx_digest_fkey;
+ALTER TABLE balance_changes DROP CONSTRAINT IF EXISTS balance_changes_tx_digest_fkey;
@@ -0,0 +1,14 @@
+-- Running coin balance per owner and coin type, summed from the balance
+-- changes of matched transactions in checkpoint order by a sequential pipeline
+CREATE TABLE balances (
+    owner VARCHAR NOT NULL,
+    coin_type VARCHAR NOT NULL,
+    balance NUMERIC NOT NULL,
+    checkpoint_sequence_number BIGINT NOT NULL,
+    timestamp_ms BIGINT NOT NULL,
+    epoch BIGINT NOT NULL,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+    PRIMARY KEY (owner, coin_type)
+);
+
+CREATE INDEX idx_balances_coin_type ON balances(coin_type);
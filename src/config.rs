@@ -0,0 +1,139 @@
+use std::path::Path;
+
+use anyhow::{anyhow, Context};
+use serde::Deserialize;
+use sui_indexer_alt_framework::pipeline::{
+    concurrent::ConcurrentConfig,
+    sequential::SequentialConfig,
+    CommitterConfig,
+};
+
+// Committer settings for the indexer's pipelines. Every field is optional;
+// unset fields fall back to the framework's defaults. Values can come from
+// CLI flags, `PIPELINE_*` environment variables, or a TOML config file:
+//
+//   max_rows_per_insert = 500
+//   write_concurrency = 2
+//   collect_interval_ms = 500
+//   watermark_interval_ms = 1000
+//   checkpoint_lag = 0
+//   sequential = ["objects", "balances"]
+#[derive(clap::Args, Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct PipelineConfig {
+    /// Maximum number of rows written per INSERT statement. This only splits
+    /// a commit into smaller statements; how many checkpoints go into a
+    /// commit is up to the framework.
+    #[arg(long, env = "PIPELINE_MAX_ROWS_PER_INSERT")]
+    pub max_rows_per_insert: Option<usize>,
+
+    /// Number of concurrent committer tasks writing to the database.
+    #[arg(long, env = "PIPELINE_WRITE_CONCURRENCY")]
+    pub write_concurrency: Option<usize>,
+
+    /// How many checkpoints sequential pipelines stay behind the tip.
+    #[arg(long, env = "PIPELINE_CHECKPOINT_LAG")]
+    pub checkpoint_lag: Option<u64>,
+
+    /// How often the collector gathers processed values into a batch.
+    #[arg(long, env = "PIPELINE_COLLECT_INTERVAL_MS")]
+    pub collect_interval_ms: Option<u64>,
+
+    /// How often the committer updates the pipeline's watermark.
+    #[arg(long, env = "PIPELINE_WATERMARK_INTERVAL_MS")]
+    pub watermark_interval_ms: Option<u64>,
+
+    /// Derived state tables maintained by a sequential pipeline, committing
+    /// checkpoints in order (objects, balances).
+    #[arg(long, env = "PIPELINE_SEQUENTIAL", value_delimiter = ',')]
+    pub sequential: Option<Vec<StateTable>>,
+}
+
+// Tables holding the latest state derived from matched transactions. Their
+// rows are updated in place, so they can be written by a sequential pipeline
+// to reflect checkpoint order.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StateTable {
+    // Latest version of each object, also kept by a concurrent pipeline
+    Objects,
+    // Running balance per owner and coin type, only kept sequentially
+    Balances,
+}
+
+impl std::str::FromStr for StateTable {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> anyhow::Result<Self> {
+        match s {
+            "objects" => Ok(StateTable::Objects),
+            "balances" => Ok(StateTable::Balances),
+            _ => Err(anyhow!("Unknown state table '{}', expected objects or balances", s)),
+        }
+    }
+}
+
+impl PipelineConfig {
+    pub fn from_file(path: &Path) -> anyhow::Result<Self> {
+        let contents = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read config file {}", path.display()))?;
+
+        toml::from_str(&contents)
+            .with_context(|| format!("Failed to parse config file {}", path.display()))
+    }
+
+    // Fill any unset fields from `fallback`.
+    pub fn or(self, fallback: Self) -> Self {
+        Self {
+            max_rows_per_insert: self.max_rows_per_insert.or(fallback.max_rows_per_insert),
+            write_concurrency: self.write_concurrency.or(fallback.write_concurrency),
+            checkpoint_lag: self.checkpoint_lag.or(fallback.checkpoint_lag),
+            collect_interval_ms: self.collect_interval_ms.or(fallback.collect_interval_ms),
+            watermark_interval_ms: self.watermark_interval_ms.or(fallback.watermark_interval_ms),
+            sequential: self.sequential.or(fallback.sequential),
+        }
+    }
+
+    // Whether `table` is maintained by a sequential pipeline.
+    pub fn is_sequential(&self, table: StateTable) -> bool {
+        self.sequential.as_ref().is_some_and(|tables| tables.contains(&table))
+    }
+
+    pub fn concurrent_config(&self) -> ConcurrentConfig {
+        ConcurrentConfig {
+            committer: self.committer_config(),
+            ..Default::default()
+        }
+    }
+
+    pub fn sequential_config(&self) -> SequentialConfig {
+        let mut config = SequentialConfig {
+            committer: self.committer_config(),
+            ..Default::default()
+        };
+
+        if let Some(checkpoint_lag) = self.checkpoint_lag {
+            config.checkpoint_lag = checkpoint_lag;
+        }
+
+        config
+    }
+
+    fn committer_config(&self) -> CommitterConfig {
+        let mut committer = CommitterConfig::default();
+
+        if let Some(write_concurrency) = self.write_concurrency {
+            committer.write_concurrency = write_concurrency;
+        }
+
+        if let Some(collect_interval_ms) = self.collect_interval_ms {
+            committer.collect_interval_ms = collect_interval_ms;
+        }
+
+        if let Some(watermark_interval_ms) = self.watermark_interval_ms {
+            committer.watermark_interval_ms = watermark_interval_ms;
+        }
+
+        committer
+    }
+}
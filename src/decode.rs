@@ -0,0 +1,186 @@
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+
+use anyhow::Context;
+use move_core_types::{
+    account_address::AccountAddress,
+    annotated_value::{MoveStruct, MoveValue},
+    language_storage::TypeTag,
+};
+use serde_json::{json, Value};
+use sui_package_resolver::{error::Error as ResolverError, Package, PackageStore, Resolver};
+use sui_types::{base_types::ObjectID, move_package::MovePackage};
+use tracing::debug;
+
+// In-memory store of every package the indexer knows about: the system
+// packages bundled with the framework, packages loaded from the `packages`
+// table, and packages published in processed checkpoints.
+#[derive(Clone, Default)]
+pub struct PackageCache {
+    packages: Arc<RwLock<HashMap<AccountAddress, Arc<Package>>>>,
+    // Original ID of every known package version, including ones that could
+    // not be read for decoding
+    originals: Arc<RwLock<HashMap<ObjectID, ObjectID>>>,
+}
+
+impl PackageCache {
+    pub fn with_system_packages() -> anyhow::Result<Self> {
+        let cache = Self::default();
+        for package in sui_framework::BuiltInFramework::iter_system_packages() {
+            cache.insert(&package.genesis_move_package())?;
+        }
+
+        Ok(cache)
+    }
+
+    pub fn insert(&self, package: &MovePackage) -> anyhow::Result<()> {
+        self.originals
+            .write()
+            .unwrap()
+            .insert(package.id(), package.original_package_id());
+
+        let id = AccountAddress::from(package.id());
+        let package = Package::read_from_package(package)
+            .with_context(|| format!("Failed to read package {}", id))?;
+
+        self.packages.write().unwrap().insert(id, Arc::new(package));
+        Ok(())
+    }
+
+    pub fn contains(&self, id: &ObjectID) -> bool {
+        self.packages.read().unwrap().contains_key(&AccountAddress::from(*id))
+    }
+
+    // The ID the package at `id` was first published at, if the package is
+    // known.
+    pub fn original_id(&self, id: &ObjectID) -> Option<ObjectID> {
+        self.originals.read().unwrap().get(id).copied()
+    }
+}
+
+#[async_trait::async_trait]
+impl PackageStore for PackageCache {
+    async fn fetch(&self, id: AccountAddress) -> sui_package_resolver::Result<Arc<Package>> {
+        self.packages
+            .read()
+            .unwrap()
+            .get(&id)
+            .cloned()
+            .ok_or(ResolverError::PackageNotFound(id))
+    }
+}
+
+// Decodes BCS-encoded Move values into JSON using layouts resolved from the
+// package cache.
+pub struct MoveDecoder {
+    cache: PackageCache,
+    resolver: Resolver<PackageCache>,
+}
+
+impl MoveDecoder {
+    pub fn new(cache: PackageCache) -> Self {
+        Self {
+            resolver: Resolver::new(cache.clone()),
+            cache,
+        }
+    }
+
+    pub fn cache(&self) -> &PackageCache {
+        &self.cache
+    }
+
+    // Decode `bytes` as a value of type `tag`. Returns `None` when the layout
+    // cannot be resolved, e.g. because the defining package was published
+    // before the indexer's first checkpoint. This happens for every value of
+    // such a type, so the reason is only logged at debug level.
+    pub fn decode(&self, tag: &TypeTag, bytes: &[u8]) -> Option<Value> {
+        // The cache never waits on I/O, so resolving the layout completes on
+        // the first poll.
+        let layout = match futures::executor::block_on(self.resolver.type_layout(tag.clone())) {
+            Ok(layout) => layout,
+            Err(e) => {
+                debug!("Failed to resolve layout for {}: {}", tag, e);
+                return None;
+            }
+        };
+
+        match MoveValue::simple_deserialize(bytes, &layout) {
+            Ok(value) => Some(move_value_to_json(value)),
+            Err(e) => {
+                debug!("Failed to decode value of type {}: {}", tag, e);
+                None
+            }
+        }
+    }
+}
+
+// Convert an annotated Move value to JSON. Integers wider than 32 bits are
+// rendered as strings to survive JSON number precision, and common framework
+// wrappers (strings, UID/ID, Option) are flattened.
+pub fn move_value_to_json(value: MoveValue) -> Value {
+    match value {
+        MoveValue::Bool(b) => json!(b),
+        MoveValue::U8(n) => json!(n),
+        MoveValue::U16(n) => json!(n),
+        MoveValue::U32(n) => json!(n),
+        MoveValue::U64(n) => json!(n.to_string()),
+        MoveValue::U128(n) => json!(n.to_string()),
+        MoveValue::U256(n) => json!(n.to_string()),
+        MoveValue::Address(a) | MoveValue::Signer(a) => json!(ObjectID::from(a).to_string()),
+        MoveValue::Vector(values) => {
+            Value::Array(values.into_iter().map(move_value_to_json).collect())
+        }
+        MoveValue::Struct(s) => struct_to_json(s),
+        MoveValue::Variant(v) => {
+            let mut fields = serde_json::Map::new();
+            for (name, value) in v.fields {
+                fields.insert(name.to_string(), move_value_to_json(value));
+            }
+            json!({ "variant": v.variant_name.to_string(), "fields": fields })
+        }
+    }
+}
+
+fn struct_to_json(s: MoveStruct) -> Value {
+    let is = |address: AccountAddress, module: &str, name: &str| {
+        s.type_.address == address
+            && s.type_.module.as_str() == module
+            && s.type_.name.as_str() == name
+    };
+
+    if is(AccountAddress::ONE, "string", "String") || is(AccountAddress::ONE, "ascii", "String") {
+        if let Some((_, MoveValue::Vector(bytes))) = s.fields.first() {
+            let bytes: Vec<u8> = bytes
+                .iter()
+                .filter_map(|b| match b {
+                    MoveValue::U8(b) => Some(*b),
+                    _ => None,
+                })
+                .collect();
+            return json!(String::from_utf8_lossy(&bytes));
+        }
+    }
+
+    if is(AccountAddress::TWO, "object", "UID") || is(AccountAddress::TWO, "object", "ID") {
+        // UID wraps an ID, which wraps an address
+        return s
+            .fields
+            .into_iter()
+            .next()
+            .map(|(_, value)| move_value_to_json(value))
+            .unwrap_or(Value::Null);
+    }
+
+    if is(AccountAddress::ONE, "option", "Option") {
+        if let Some((_, MoveValue::Vector(mut values))) = s.fields.into_iter().next() {
+            return values.pop().map(move_value_to_json).unwrap_or(Value::Null);
+        }
+        return Value::Null;
+    }
+
+    let mut fields = serde_json::Map::new();
+    for (name, value) in s.fields {
+        fields.insert(name.to_string(), move_value_to_json(value));
+    }
+    Value::Object(fields)
+}
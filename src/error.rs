@@ -0,0 +1,58 @@
+use std::fmt;
+
+// Failure returned by `SuiIndexer::start`, classified by the stage that
+// failed. Each variant wraps the underlying error with its context, which is
+// exposed as the error's `source`.
+#[derive(Debug)]
+pub enum IndexerError {
+    // Invalid filters, pipeline settings or cluster arguments, or a pipeline
+    // the framework refused to register
+    Config(anyhow::Error),
+    // Connecting to, migrating or loading indexer state from the database
+    Database(anyhow::Error),
+    // Starting ingestion, the indexer task panicking, or the indexer stopping
+    // before every pipeline reached the last checkpoint
+    Ingestion(anyhow::Error),
+    // Loading the packages used to decode Move values
+    Decode(anyhow::Error),
+}
+
+impl IndexerError {
+    // Process exit code for this kind of failure. Codes 1 and 2 are left to
+    // argument and config file errors reported before the indexer starts.
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            IndexerError::Config(_) => 3,
+            IndexerError::Database(_) => 4,
+            IndexerError::Ingestion(_) => 5,
+            IndexerError::Decode(_) => 6,
+        }
+    }
+
+    fn inner(&self) -> &anyhow::Error {
+        match self {
+            IndexerError::Config(e)
+            | IndexerError::Database(e)
+            | IndexerError::Ingestion(e)
+            | IndexerError::Decode(e) => e,
+        }
+    }
+}
+
+impl fmt::Display for IndexerError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // The cause is left to `source`, so reporters print it once
+        match self {
+            IndexerError::Config(_) => write!(f, "configuration error"),
+            IndexerError::Database(_) => write!(f, "database error"),
+            IndexerError::Ingestion(_) => write!(f, "ingestion error"),
+            IndexerError::Decode(_) => write!(f, "decode error"),
+        }
+    }
+}
+
+impl std::error::Error for IndexerError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(&**self.inner())
+    }
+}
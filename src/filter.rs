@@ -0,0 +1,603 @@
+use std::fmt;
+use std::str::FromStr;
+
+use anyhow::anyhow;
+use move_core_types::language_storage::StructTag;
+use sui_types::{
+    base_types::{ObjectID, SuiAddress},
+    effects::TransactionEffectsAPI,
+    full_checkpoint_content::CheckpointTransaction,
+    transaction::TransactionDataAPI,
+};
+
+// A single segment of a filter expression: either a literal or a glob where
+// `*` matches any run of characters.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Pattern {
+    Any,
+    Address(SuiAddress),
+    Glob(String),
+}
+
+impl Pattern {
+    fn parse(segment: &str, is_address: bool) -> anyhow::Result<Self> {
+        if segment.is_empty() {
+            return Err(anyhow!("Empty segment in filter expression"));
+        }
+
+        if segment == "*" {
+            return Ok(Pattern::Any);
+        }
+
+        if is_address && !segment.contains('*') {
+            let address = parse_address(segment)
+                .map_err(|e| anyhow!("Invalid package address '{}': {}", segment, e))?;
+            return Ok(Pattern::Address(address));
+        }
+
+        Ok(Pattern::Glob(segment.to_string()))
+    }
+
+    pub fn matches(&self, value: &str) -> bool {
+        match self {
+            Pattern::Any => true,
+            Pattern::Address(address) => parse_address(value).is_ok_and(|v| v == *address),
+            Pattern::Glob(glob) => glob_matches(glob, value),
+        }
+    }
+
+    // Match a package segment against a package ID or, for an upgraded
+    // version, the ID it was first published at.
+    fn matches_package(&self, package: &ObjectID, original: &ObjectID) -> bool {
+        self.matches(&package.to_string())
+            || (original != package && self.matches(&original.to_string()))
+    }
+}
+
+impl fmt::Display for Pattern {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Pattern::Any => write!(f, "*"),
+            Pattern::Address(address) => write!(f, "{}", address),
+            Pattern::Glob(glob) => write!(f, "{}", glob),
+        }
+    }
+}
+
+// Parse an address in full or in the short form used for system packages,
+// e.g. `0x2`, which `SuiAddress` itself rejects.
+fn parse_address(s: &str) -> anyhow::Result<SuiAddress> {
+    Ok(SuiAddress::from(ObjectID::from_str(s)?))
+}
+
+// Match `value` against a glob where `*` matches zero or more characters.
+fn glob_matches(glob: &str, value: &str) -> bool {
+    let mut parts = glob.split('*');
+    let first = parts.next().unwrap_or_default();
+    let Some(mut rest) = value.strip_prefix(first) else {
+        return false;
+    };
+
+    let parts: Vec<&str> = parts.collect();
+    let Some((last, middle)) = parts.split_last() else {
+        // No wildcard at all, so the literal must match exactly
+        return rest.is_empty();
+    };
+
+    for part in middle {
+        match rest.find(part) {
+            Some(pos) => rest = &rest[pos + part.len()..],
+            None => return false,
+        }
+    }
+
+    rest.ends_with(last)
+}
+
+// Filter on a move call written as `package::module::function`. Each segment
+// may be `*` or a glob, e.g. `0x2::pool::*` or `*::pool::add_*`. Trailing
+// segments may be omitted, so `0x2::pool` matches every function in `pool`.
+// A package given by its original ID also matches calls to its upgrades.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MoveCallFilter {
+    pub package: Pattern,
+    pub module: Pattern,
+    pub function: Pattern,
+}
+
+impl MoveCallFilter {
+    pub fn matches(&self, package: &ObjectID, original: &ObjectID, module: &str, function: &str) -> bool {
+        self.package.matches_package(package, original)
+            && self.module.matches(module)
+            && self.function.matches(function)
+    }
+}
+
+impl FromStr for MoveCallFilter {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> anyhow::Result<Self> {
+        let [package, module, function] = parse_path(s)?;
+        Ok(Self { package, module, function })
+    }
+}
+
+impl fmt::Display for MoveCallFilter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}::{}::{}", self.package, self.module, self.function)
+    }
+}
+
+// Filter on a struct type written as `package::module::Name`, with the same
+// wildcard and upgrade rules as `MoveCallFilter`. A type added in an upgrade
+// has the address of the version that added it, so it is matched through
+// that version's original ID.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StructTypeFilter {
+    pub package: Pattern,
+    pub module: Pattern,
+    pub name: Pattern,
+}
+
+impl StructTypeFilter {
+    pub fn matches(&self, tag: &StructTag, original: &ObjectID) -> bool {
+        self.package.matches_package(&ObjectID::from(tag.address), original)
+            && self.module.matches(tag.module.as_str())
+            && self.name.matches(tag.name.as_str())
+    }
+}
+
+impl FromStr for StructTypeFilter {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> anyhow::Result<Self> {
+        let [package, module, name] = parse_path(s)?;
+        Ok(Self { package, module, name })
+    }
+}
+
+impl fmt::Display for StructTypeFilter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}::{}::{}", self.package, self.module, self.name)
+    }
+}
+
+// Split `package::module::name` into patterns, filling omitted trailing
+// segments with `*`.
+fn parse_path(s: &str) -> anyhow::Result<[Pattern; 3]> {
+    let segments: Vec<&str> = s.trim().split("::").collect();
+    if segments.len() > 3 {
+        return Err(anyhow!(
+            "Invalid filter path '{}', expected package::module::name", s
+        ));
+    }
+
+    let segment = |i: usize| segments.get(i).copied().unwrap_or("*");
+
+    Ok([
+        Pattern::parse(segment(0), true)?,
+        Pattern::parse(segment(1), false)?,
+        Pattern::parse(segment(2), false)?,
+    ])
+}
+
+// Composable predicate over a transaction in a checkpoint.
+//
+// The textual syntax accepted by `FromStr` is:
+//
+//   expr  := or
+//   or    := and ("or" and)*
+//   and   := unary ("and" unary)*
+//   unary := "not" unary | "(" expr ")" | atom
+//   atom  := package(ADDR) | module(PKG::MOD) | function(PKG::MOD::FUN)
+//          | sender(ADDR) | event(PKG::MOD::TYPE) | object(PKG::MOD::TYPE)
+//          | checkpoint(FROM..TO) | success | failed
+//
+// Paths accept `*` wildcards, and either end of a checkpoint range may be
+// left open. For example:
+//
+//   (module(0x2::pool) or event(0x2::pool::SwapEvent)) and not failed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Filter {
+    // Any move call matches the `package::module::function` pattern
+    MoveCall(MoveCallFilter),
+    Sender(SuiAddress),
+    // Any emitted event's type matches
+    EventType(StructTypeFilter),
+    // Any input or output object's type matches
+    ObjectType(StructTypeFilter),
+    // Checkpoint sequence number within `[from, to]`
+    CheckpointRange { from: Option<u64>, to: Option<u64> },
+    // Execution succeeded (`true`) or failed (`false`)
+    Status(bool),
+    And(Vec<Filter>),
+    Or(Vec<Filter>),
+    Not(Box<Filter>),
+}
+
+impl Filter {
+    // `original_id` maps a package version to the ID it was first published
+    // at, so that package patterns also match upgraded versions.
+    pub fn matches(
+        &self,
+        tx: &CheckpointTransaction,
+        checkpoint: u64,
+        original_id: &dyn Fn(&ObjectID) -> ObjectID,
+    ) -> bool {
+        let type_matches = |filter: &StructTypeFilter, tag: &StructTag| {
+            filter.matches(tag, &original_id(&ObjectID::from(tag.address)))
+        };
+
+        match self {
+            Filter::MoveCall(filter) => tx
+                .transaction
+                .transaction_data()
+                .move_calls()
+                .into_iter()
+                .any(|(package, module, function)| {
+                    filter.matches(package, &original_id(package), module, function)
+                }),
+            Filter::Sender(sender) => tx.transaction.transaction_data().sender() == *sender,
+            Filter::EventType(filter) => tx
+                .events
+                .iter()
+                .flat_map(|e| e.data.iter())
+                .any(|event| type_matches(filter, &event.type_)),
+            Filter::ObjectType(filter) => tx
+                .input_objects
+                .iter()
+                .chain(tx.output_objects.iter())
+                .filter_map(|o| o.struct_tag())
+                .any(|tag| type_matches(filter, &tag)),
+            Filter::CheckpointRange { from, to } => checkpoint_in_range(*from, *to, checkpoint),
+            Filter::Status(success) => tx.effects.status().is_ok() == *success,
+            Filter::And(filters) => filters.iter().all(|f| f.matches(tx, checkpoint, original_id)),
+            Filter::Or(filters) => filters.iter().any(|f| f.matches(tx, checkpoint, original_id)),
+            Filter::Not(filter) => !filter.matches(tx, checkpoint, original_id),
+        }
+    }
+}
+
+// Whether `checkpoint` is within `[from, to]`, where a missing bound is open.
+fn checkpoint_in_range(from: Option<u64>, to: Option<u64>, checkpoint: u64) -> bool {
+    from.is_none_or(|from| checkpoint >= from) && to.is_none_or(|to| checkpoint <= to)
+}
+
+impl FromStr for Filter {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> anyhow::Result<Self> {
+        let mut parser = Parser { input: s, pos: 0 };
+        let filter = parser.parse_or()?;
+
+        parser.skip_whitespace();
+        if parser.pos < parser.input.len() {
+            return Err(anyhow!(
+                "Unexpected '{}' at position {} in filter", &parser.input[parser.pos..], parser.pos
+            ));
+        }
+
+        Ok(filter)
+    }
+}
+
+impl fmt::Display for Filter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let join = |f: &mut fmt::Formatter<'_>, filters: &[Filter], op: &str| {
+            write!(f, "(")?;
+            for (i, filter) in filters.iter().enumerate() {
+                if i > 0 {
+                    write!(f, " {} ", op)?;
+                }
+                write!(f, "{}", filter)?;
+            }
+            write!(f, ")")
+        };
+
+        match self {
+            Filter::MoveCall(filter) => write!(f, "function({})", filter),
+            Filter::Sender(sender) => write!(f, "sender({})", sender),
+            Filter::EventType(filter) => write!(f, "event({})", filter),
+            Filter::ObjectType(filter) => write!(f, "object({})", filter),
+            Filter::CheckpointRange { from, to } => {
+                write!(f, "checkpoint(")?;
+                if let Some(from) = from {
+                    write!(f, "{}", from)?;
+                }
+                write!(f, "..")?;
+                if let Some(to) = to {
+                    write!(f, "{}", to)?;
+                }
+                write!(f, ")")
+            }
+            Filter::Status(true) => write!(f, "success"),
+            Filter::Status(false) => write!(f, "failed"),
+            Filter::And(filters) => join(f, filters, "and"),
+            Filter::Or(filters) => join(f, filters, "or"),
+            Filter::Not(filter) => write!(f, "not {}", filter),
+        }
+    }
+}
+
+// Recursive descent parser for the filter syntax described on `Filter`.
+struct Parser<'a> {
+    input: &'a str,
+    pos: usize,
+}
+
+impl Parser<'_> {
+    fn skip_whitespace(&mut self) {
+        let rest = &self.input[self.pos..];
+        self.pos += rest.len() - rest.trim_start().len();
+    }
+
+    fn peek_char(&mut self) -> Option<char> {
+        self.skip_whitespace();
+        self.input[self.pos..].chars().next()
+    }
+
+    fn expect_char(&mut self, expected: char) -> anyhow::Result<()> {
+        match self.peek_char() {
+            Some(c) if c == expected => {
+                self.pos += c.len_utf8();
+                Ok(())
+            }
+            _ => Err(anyhow!("Expected '{}' at position {} in filter", expected, self.pos)),
+        }
+    }
+
+    // Read the next identifier without consuming it
+    fn peek_ident(&mut self) -> &str {
+        self.skip_whitespace();
+        let rest = &self.input[self.pos..];
+        let end = rest
+            .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+            .unwrap_or(rest.len());
+        &rest[..end]
+    }
+
+    fn eat_keyword(&mut self, keyword: &str) -> bool {
+        if self.peek_ident().eq_ignore_ascii_case(keyword) {
+            self.pos += keyword.len();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn parse_or(&mut self) -> anyhow::Result<Filter> {
+        let mut filters = vec![self.parse_and()?];
+        while self.eat_keyword("or") {
+            filters.push(self.parse_and()?);
+        }
+
+        Ok(if filters.len() == 1 { filters.remove(0) } else { Filter::Or(filters) })
+    }
+
+    fn parse_and(&mut self) -> anyhow::Result<Filter> {
+        let mut filters = vec![self.parse_unary()?];
+        while self.eat_keyword("and") {
+            filters.push(self.parse_unary()?);
+        }
+
+        Ok(if filters.len() == 1 { filters.remove(0) } else { Filter::And(filters) })
+    }
+
+    fn parse_unary(&mut self) -> anyhow::Result<Filter> {
+        if self.eat_keyword("not") {
+            return Ok(Filter::Not(Box::new(self.parse_unary()?)));
+        }
+
+        if self.peek_char() == Some('(') {
+            self.expect_char('(')?;
+            let filter = self.parse_or()?;
+            self.expect_char(')')?;
+            return Ok(filter);
+        }
+
+        self.parse_atom()
+    }
+
+    fn parse_atom(&mut self) -> anyhow::Result<Filter> {
+        let start = self.pos;
+        let name = self.peek_ident().to_ascii_lowercase();
+        if name.is_empty() {
+            return Err(anyhow!("Expected a filter at position {}", start));
+        }
+        self.pos += name.len();
+
+        match name.as_str() {
+            "success" => return Ok(Filter::Status(true)),
+            "failed" => return Ok(Filter::Status(false)),
+            _ => {}
+        }
+
+        self.expect_char('(')?;
+        let rest = &self.input[self.pos..];
+        let end = rest
+            .find(')')
+            .ok_or_else(|| anyhow!("Unclosed '(' after '{}' in filter", name))?;
+        let arg = rest[..end].trim();
+        self.pos += end + 1;
+
+        match name.as_str() {
+            "package" => Ok(Filter::MoveCall(MoveCallFilter {
+                package: Pattern::parse(arg, true)?,
+                module: Pattern::Any,
+                function: Pattern::Any,
+            })),
+            "module" | "function" => Ok(Filter::MoveCall(arg.parse()?)),
+            "sender" => Ok(Filter::Sender(
+                parse_address(arg).map_err(|e| anyhow!("Invalid sender address '{}': {}", arg, e))?,
+            )),
+            "event" => Ok(Filter::EventType(arg.parse()?)),
+            "object" => Ok(Filter::ObjectType(arg.parse()?)),
+            "checkpoint" => {
+                let (from, to) = arg
+                    .split_once("..")
+                    .ok_or_else(|| anyhow!("Invalid checkpoint range '{}', expected FROM..TO", arg))?;
+                let bound = |b: &str| -> anyhow::Result<Option<u64>> {
+                    let b = b.trim();
+                    if b.is_empty() {
+                        Ok(None)
+                    } else {
+                        Ok(Some(b.parse().map_err(|e| anyhow!("Invalid checkpoint '{}': {}", b, e))?))
+                    }
+                };
+                Ok(Filter::CheckpointRange { from: bound(from)?, to: bound(to)? })
+            }
+            _ => Err(anyhow!("Unknown filter '{}' at position {}", name, start)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn address(byte: u8) -> SuiAddress {
+        SuiAddress::from(ObjectID::from_single_byte(byte))
+    }
+
+    fn parse(s: &str) -> Filter {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn and_binds_tighter_than_or() {
+        let a = address(1);
+        assert_eq!(
+            parse(&format!("success or sender({a}) and failed")),
+            Filter::Or(vec![
+                Filter::Status(true),
+                Filter::And(vec![Filter::Sender(a), Filter::Status(false)]),
+            ]),
+        );
+    }
+
+    #[test]
+    fn parentheses_override_precedence() {
+        let a = address(1);
+        assert_eq!(
+            parse(&format!("(success or sender({a})) and failed")),
+            Filter::And(vec![
+                Filter::Or(vec![Filter::Status(true), Filter::Sender(a)]),
+                Filter::Status(false),
+            ]),
+        );
+    }
+
+    #[test]
+    fn not_applies_to_the_next_operand() {
+        assert_eq!(
+            parse("not success and failed"),
+            Filter::And(vec![Filter::Not(Box::new(Filter::Status(true))), Filter::Status(false)]),
+        );
+        assert_eq!(
+            parse("not (success and failed)"),
+            Filter::Not(Box::new(Filter::And(vec![Filter::Status(true), Filter::Status(false)]))),
+        );
+        assert_eq!(
+            parse("NOT not failed"),
+            Filter::Not(Box::new(Filter::Not(Box::new(Filter::Status(false))))),
+        );
+    }
+
+    #[test]
+    fn display_round_trips() {
+        let a = address(1);
+        let filter = parse(&format!(
+            "(module({a}::pool) or event(*::pool::Swap*)) and not failed and checkpoint(10..)"
+        ));
+        assert_eq!(parse(&filter.to_string()), filter);
+    }
+
+    #[test]
+    fn checkpoint_ranges_may_be_open() {
+        let range = |from, to| Filter::CheckpointRange { from, to };
+        assert_eq!(parse("checkpoint(10..20)"), range(Some(10), Some(20)));
+        assert_eq!(parse("checkpoint( 10 .. )"), range(Some(10), None));
+        assert_eq!(parse("checkpoint(..20)"), range(None, Some(20)));
+        assert_eq!(parse("checkpoint(..)"), range(None, None));
+
+        assert!("checkpoint(10)".parse::<Filter>().is_err());
+        assert!("checkpoint(a..)".parse::<Filter>().is_err());
+    }
+
+    #[test]
+    fn open_bounds_include_everything_on_their_side() {
+        assert!(checkpoint_in_range(None, None, 0));
+        assert!(checkpoint_in_range(None, None, u64::MAX));
+        assert!(!checkpoint_in_range(Some(10), None, 9));
+        assert!(checkpoint_in_range(Some(10), None, 10));
+        assert!(checkpoint_in_range(Some(10), None, u64::MAX));
+        assert!(checkpoint_in_range(None, Some(20), 0));
+        assert!(checkpoint_in_range(None, Some(20), 20));
+        assert!(!checkpoint_in_range(None, Some(20), 21));
+    }
+
+    #[test]
+    fn glob_edge_cases() {
+        assert!(glob_matches("*", ""));
+        assert!(glob_matches("**", "anything"));
+        assert!(glob_matches("add_*", "add_"));
+        assert!(glob_matches("*_liquidity", "add_liquidity"));
+        assert!(glob_matches("a*b*c", "abc"));
+        assert!(glob_matches("*b*", "abc"));
+        assert!(!glob_matches("swap", "swap_exact"));
+        assert!(!glob_matches("swap", "swa"));
+        // A prefix and suffix may not share characters
+        assert!(!glob_matches("a*a", "a"));
+        assert!(!glob_matches("ab*bc", "abc"));
+        assert!(glob_matches("ab*bc", "abbc"));
+    }
+
+    #[test]
+    fn documented_examples_parse() {
+        let pool = MoveCallFilter {
+            package: Pattern::Address(address(2)),
+            module: Pattern::Glob("pool".to_string()),
+            function: Pattern::Any,
+        };
+        let swap_event = StructTypeFilter {
+            package: Pattern::Address(address(2)),
+            module: Pattern::Glob("pool".to_string()),
+            name: Pattern::Glob("SwapEvent".to_string()),
+        };
+        assert_eq!(
+            parse("(module(0x2::pool) or event(0x2::pool::SwapEvent)) and not failed"),
+            Filter::And(vec![
+                Filter::Or(vec![Filter::MoveCall(pool.clone()), Filter::EventType(swap_event)]),
+                Filter::Not(Box::new(Filter::Status(false))),
+            ]),
+        );
+
+        assert_eq!("0x2::pool".parse::<MoveCallFilter>().unwrap(), pool);
+        assert_eq!("0x2::pool::*".parse::<MoveCallFilter>().unwrap(), pool);
+        assert_eq!(
+            "*::pool::add_*".parse::<MoveCallFilter>().unwrap(),
+            MoveCallFilter {
+                package: Pattern::Any,
+                module: Pattern::Glob("pool".to_string()),
+                function: Pattern::Glob("add_*".to_string()),
+            },
+        );
+        assert_eq!(parse("sender(0x2)"), Filter::Sender(address(2)));
+        assert_eq!(parse(&format!("package({})", address(2))), parse("package(0x2)"));
+    }
+
+    #[test]
+    fn package_pattern_matches_original_id() {
+        let (original, upgraded, other) = (
+            ObjectID::from_single_byte(1),
+            ObjectID::from_single_byte(2),
+            ObjectID::from_single_byte(3),
+        );
+        let filter: MoveCallFilter = format!("{original}::pool::swap").parse().unwrap();
+
+        assert!(filter.matches(&original, &original, "pool", "swap"));
+        assert!(filter.matches(&upgraded, &original, "pool", "swap"));
+        assert!(!filter.matches(&other, &other, "pool", "swap"));
+        assert!(!filter.matches(&upgraded, &original, "pool", "add"));
+    }
+}
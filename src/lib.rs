@@ -1,38 +1,59 @@
-use std::sync::Arc;
-use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+use std::collections::{BTreeSet, HashMap};
 use url::Url;
 use anyhow::anyhow;
 use diesel_migrations::{embed_migrations, EmbeddedMigrations};
-use diesel::prelude::*;
-use diesel_async::RunQueryDsl;
-use tracing::info;
+use diesel_async::{AsyncConnection, AsyncPgConnection, RunQueryDsl};
+use diesel_async::pooled_connection::{bb8::RunError, PoolError};
+use tracing::{info, warn};
 
 pub mod schema;
 pub mod models;
+pub mod filter;
+pub mod decode;
+pub mod config;
+pub mod matcher;
+pub mod pipelines;
+pub mod ordered;
+pub mod error;
 
-use crate::models::{MyIndexData, Transaction, TransactionEffect, TransactionEvent, InputObjects, OutputObjects};
+use crate::config::{PipelineConfig, StateTable};
+use crate::decode::{MoveDecoder, PackageCache};
+use crate::filter::{Filter, MoveCallFilter};
+use crate::matcher::{TransactionMatcher, RETAINED_CHECKPOINTS};
+use crate::models::{MyIndexData, Package, PackageLineage};
+use crate::ordered::InOrder;
+use crate::pipelines::{
+    BalanceChangesPipeline, BalancesPipeline, CheckpointFeedPipeline, CheckpointTransactionsPipeline, CheckpointsPipeline,
+    CustomIndexPipeline,
+    EffectsPipeline, EventsPipeline, InputObjectsPipeline, LiveObjectsPipeline, MoveCallsPipeline,
+    ObjectsHistoryPipeline, OutputObjectsPipeline, PackageLineagePipeline, PackagesPipeline,
+    TransactionEventsPipeline, TransactionObjectsPipeline, TransactionsPipeline,
+};
+
+pub use crate::error::IndexerError;
+pub use crate::pipelines::{rows_per_insert, MAX_BIND_PARAMS};
 
 // Embed the migrations in the library
 pub const MIGRATIONS: EmbeddedMigrations = embed_migrations!("migrations");
 
 use sui_indexer_alt_framework::{
     cluster::{self, IndexerCluster}, 
-    db, 
     pipeline::{
-        concurrent::{ConcurrentConfig, Handler as ConcurrentHandler}, 
-        Processor
-    }, 
+        concurrent::{ConcurrentConfig, Handler as ConcurrentHandler},
+        sequential::{Handler as SequentialHandler, SequentialConfig},
+        Processor,
+    },
     types::full_checkpoint_content::CheckpointData, 
-    FieldCount, 
     Result
 };
+use anyhow::Context;
 
-use sui_types::{
-    base_types::{ObjectID, SuiAddress}, 
-    transaction::{TransactionDataAPI, Command, TransactionKind}
-};
+use sui_types::base_types::SuiAddress;
+use sui_types::move_package::MovePackage;
 
-// Enum to specify which fields to index
+// Enum to specify which fields to index. Each selected field registers the
+// pipelines writing its tables; an empty selection indexes every field.
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum IndexField {
     Transaction,
@@ -40,13 +61,92 @@ pub enum IndexField {
     Events,
     InputObjects,
     OutputObjects,
+    MoveCalls,
+    ObjectHistory,
+    BalanceChanges,
+}
+
+impl std::str::FromStr for IndexField {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "transaction" => Ok(IndexField::Transaction),
+            "effects" => Ok(IndexField::Effects),
+            "events" => Ok(IndexField::Events),
+            "input-objects" => Ok(IndexField::InputObjects),
+            "output-objects" => Ok(IndexField::OutputObjects),
+            "move-calls" => Ok(IndexField::MoveCalls),
+            "object-history" => Ok(IndexField::ObjectHistory),
+            "balance-changes" => Ok(IndexField::BalanceChanges),
+            _ => Err(anyhow!(
+                "Unknown index field '{}', expected transaction, effects, events, input-objects, \
+                 output-objects, move-calls, object-history or balance-changes",
+                s
+            )),
+        }
+    }
+}
+
+// How a transaction is matched against the tracked packages. Several modes
+// can be enabled at once; a transaction matches if any of them does.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MatchMode {
+    // A move call targets a tracked package
+    MoveCall,
+    // An emitted event's type is defined in a tracked package
+    Event,
+    // An input or output object's type is defined in a tracked package
+    Object,
+}
+
+impl std::str::FromStr for MatchMode {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "move-call" => Ok(MatchMode::MoveCall),
+            "event" => Ok(MatchMode::Event),
+            "object" => Ok(MatchMode::Object),
+            _ => Err(anyhow!("Unknown match mode '{}', expected move-call, event or object", s)),
+        }
+    }
+}
+
+// How the package filter and the address filters are combined when both are
+// set. When only one of them is set, it alone decides.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum FilterCombinator {
+    #[default]
+    And,
+    Or,
+}
+
+impl std::str::FromStr for FilterCombinator {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_ascii_lowercase().as_str() {
+            "and" => Ok(FilterCombinator::And),
+            "or" => Ok(FilterCombinator::Or),
+            _ => Err(anyhow!("Unknown filter combinator '{}', expected and or or", s)),
+        }
+    }
 }
 
 // Type for callback functions
 pub type IndexCallback = Box<dyn Fn(&CheckpointData) -> Result<Vec<MyIndexData>> + Send + Sync>;
 
 pub struct SuiIndexer {
-    package_filter: Option<SuiAddress>,
+    package_filters: BTreeSet<SuiAddress>,
+    call_filters: Vec<MoveCallFilter>,
+    match_modes: Vec<MatchMode>,
+    sender_filters: BTreeSet<SuiAddress>,
+    owner_filters: BTreeSet<SuiAddress>,
+    combinator: FilterCombinator,
+    filter: Option<Filter>,
+    skip_failed: bool,
+    pipeline_config: PipelineConfig,
     field_filters: Vec<IndexField>,
     field_callbacks: HashMap<IndexField, IndexCallback>,
 }
@@ -54,14 +154,71 @@ pub struct SuiIndexer {
 impl SuiIndexer {
     pub fn new() -> Self {
         Self {
-            package_filter: None,
+            package_filters: BTreeSet::new(),
+            call_filters: Vec::new(),
+            match_modes: vec![MatchMode::MoveCall],
+            sender_filters: BTreeSet::new(),
+            owner_filters: BTreeSet::new(),
+            combinator: FilterCombinator::default(),
+            filter: None,
+            skip_failed: false,
+            pipeline_config: PipelineConfig::default(),
             field_filters: Vec::new(),
             field_callbacks: HashMap::new(),
         }
     }
 
     pub fn set_filter_package(&mut self, package: SuiAddress) {
-        self.package_filter = Some(package);
+        self.package_filters = BTreeSet::from([package]);
+    }
+
+    pub fn set_filter_packages(&mut self, packages: impl IntoIterator<Item = SuiAddress>) {
+        self.package_filters = packages.into_iter().collect();
+    }
+
+    // Restrict matching to the given `package::module::function` patterns. A
+    // call must still target a tracked package; no patterns means every call
+    // to a tracked package matches.
+    pub fn set_filter_move_calls(&mut self, filters: impl IntoIterator<Item = MoveCallFilter>) {
+        self.call_filters = filters.into_iter().collect();
+    }
+
+    // Choose how transactions are matched; defaults to move calls only.
+    pub fn set_match_modes(&mut self, modes: impl IntoIterator<Item = MatchMode>) {
+        self.match_modes = modes.into_iter().collect();
+    }
+
+    // Index transactions sent by any of these addresses.
+    pub fn set_filter_senders(&mut self, senders: impl IntoIterator<Item = SuiAddress>) {
+        self.sender_filters = senders.into_iter().collect();
+    }
+
+    // Index transactions that leave an object owned by any of these addresses.
+    pub fn set_filter_owners(&mut self, owners: impl IntoIterator<Item = SuiAddress>) {
+        self.owner_filters = owners.into_iter().collect();
+    }
+
+    // Choose whether the package and address filters must both match or
+    // either one is enough.
+    pub fn set_filter_combinator(&mut self, combinator: FilterCombinator) {
+        self.combinator = combinator;
+    }
+
+    // Apply a filter expression to every transaction. It is combined with
+    // AND when package or address filters are also set, otherwise it alone
+    // decides what is indexed.
+    pub fn set_filter(&mut self, filter: Filter) {
+        self.filter = Some(filter);
+    }
+
+    // Drop failed transactions instead of indexing them with a `failure`
+    // status.
+    pub fn set_skip_failed(&mut self, skip_failed: bool) {
+        self.skip_failed = skip_failed;
+    }
+
+    pub fn set_pipeline_config(&mut self, config: PipelineConfig) {
+        self.pipeline_config = config;
     }
 
     pub fn set_filter_fields(&mut self, fields: Vec<IndexField>) {
@@ -76,277 +233,526 @@ impl SuiIndexer {
         self.field_callbacks.insert(field, Box::new(callback));
     }
 
+    // Run the indexer until every pipeline stops. Pipelines wait on each
+    // other to match checkpoints in order, blocking their worker thread while
+    // they do, so this must run on a multi-threaded tokio runtime.
     pub async fn start(
         self,
         database_url: Url,
         cluster_args: cluster::Args,
-    ) -> Result<()> {
-        let package_filter = self.package_filter
-            .ok_or_else(|| anyhow!("Package filter not set"))?;
-            
-        let pipeline = IndexerPipeline {
-            field_filters: self.field_filters,
-            package_filter,
-            callbacks: self.field_callbacks,
-        };
+    ) -> Result<(), IndexerError> {
+        if self.package_filters.is_empty()
+            && self.sender_filters.is_empty()
+            && self.owner_filters.is_empty()
+            && self.filter.is_none()
+        {
+            return Err(IndexerError::Config(anyhow!("Package filter not set")));
+        }
+
+        if self.match_modes.is_empty() {
+            return Err(IndexerError::Config(anyhow!("No match mode set")));
+        }
+
+        check_runtime()?;
+
+        let mut cluster_args = cluster_args;
+        enable_feed(&mut cluster_args.indexer_args.pipeline);
+
+        // Needed to work out where ingestion starts once the cluster owns them
+        let first_checkpoint = cluster_args.indexer_args.first_checkpoint;
+        let last_checkpoint = cluster_args.indexer_args.last_checkpoint;
+        let enabled = cluster_args.indexer_args.pipeline.clone();
+
+        // Connect before the cluster does, so an unreachable database is
+        // reported as such rather than as a cluster failure
+        let mut conn = AsyncPgConnection::establish(database_url.as_str())
+            .await
+            .context("Failed to connect to database")
+            .map_err(IndexerError::Database)?;
 
         // Initialize the cluster with our migrations
         let mut indexer = IndexerCluster::new(
-            database_url,
+            database_url.clone(),
             cluster_args,
             Some(&crate::MIGRATIONS),
-        ).await?;
-        
-        let _ = indexer.concurrent_pipeline(pipeline, ConcurrentConfig::default()).await;
-        
-        // Run the indexer
-        let _ = indexer.run().await?.await;
+        )
+        .await
+        .map_err(cluster_error)?;
+
+        // Extend the filter with upgrades recorded by previous runs
+        let package_filters = load_package_lineage(&mut conn, self.package_filters)
+            .await
+            .map_err(IndexerError::Database)?;
+
+        // Restore the packages needed to decode Move values
+        let package_cache = load_packages(&mut conn).await?;
+
+        let indexes = |field: &IndexField| self.field_filters.is_empty() || self.field_filters.contains(field);
+
+        // Only run callbacks for the selected fields
+        let callbacks: HashMap<IndexField, IndexCallback> = self
+            .field_callbacks
+            .into_iter()
+            .filter(|(field, _)| indexes(field))
+            .collect();
+
+        let matcher = Arc::new(TransactionMatcher {
+            field_filters: self.field_filters.clone(),
+            package_filters: RwLock::new(package_filters),
+            call_filters: self.call_filters,
+            match_modes: self.match_modes,
+            sender_filters: self.sender_filters,
+            owner_filters: self.owner_filters,
+            combinator: self.combinator,
+            filter: self.filter,
+            skip_failed: self.skip_failed,
+            decoder: MoveDecoder::new(package_cache),
+            ordered: InOrder::new(0, RETAINED_CHECKPOINTS),
+        });
+
+        pipelines::set_max_rows_per_insert(self.pipeline_config.max_rows_per_insert);
+
+        // Each table is written by its own pipeline with its own watermark,
+        // so a slow or failing table does not hold back the others
+        let config = &self.pipeline_config;
+        let mut registered = Vec::new();
+        add_concurrent(&mut indexer, &mut registered, CheckpointFeedPipeline(matcher.clone()), config.concurrent_config()).await?;
+        add_concurrent(&mut indexer, &mut registered, CheckpointsPipeline(matcher.clone()), config.concurrent_config()).await?;
+        add_concurrent(&mut indexer, &mut registered, PackageLineagePipeline(matcher.clone()), config.concurrent_config()).await?;
+        add_concurrent(&mut indexer, &mut registered, PackagesPipeline(matcher.clone()), config.concurrent_config()).await?;
+
+        if indexes(&IndexField::Transaction) {
+            add_concurrent(&mut indexer, &mut registered, TransactionsPipeline(matcher.clone()), config.concurrent_config()).await?;
+            add_concurrent(&mut indexer, &mut registered, CheckpointTransactionsPipeline(matcher.clone()), config.concurrent_config()).await?;
+        }
+
+        if indexes(&IndexField::Effects) {
+            add_concurrent(&mut indexer, &mut registered, EffectsPipeline(matcher.clone()), config.concurrent_config()).await?;
+        }
+
+        if indexes(&IndexField::Events) {
+            add_concurrent(&mut indexer, &mut registered, TransactionEventsPipeline(matcher.clone()), config.concurrent_config()).await?;
+            add_concurrent(&mut indexer, &mut registered, EventsPipeline(matcher.clone()), config.concurrent_config()).await?;
+        }
+
+        if indexes(&IndexField::InputObjects) {
+            add_concurrent(&mut indexer, &mut registered, InputObjectsPipeline(matcher.clone()), config.concurrent_config()).await?;
+        }
+
+        if indexes(&IndexField::OutputObjects) {
+            add_concurrent(&mut indexer, &mut registered, OutputObjectsPipeline(matcher.clone()), config.concurrent_config()).await?;
+        }
+
+        if indexes(&IndexField::InputObjects) || indexes(&IndexField::OutputObjects) {
+            add_concurrent(&mut indexer, &mut registered, TransactionObjectsPipeline(matcher.clone()), config.concurrent_config()).await?;
+        }
+
+        if indexes(&IndexField::MoveCalls) {
+            add_concurrent(&mut indexer, &mut registered, MoveCallsPipeline(matcher.clone()), config.concurrent_config()).await?;
+        }
+
+        if indexes(&IndexField::ObjectHistory) {
+            add_concurrent(&mut indexer, &mut registered, ObjectsHistoryPipeline(matcher.clone()), config.concurrent_config()).await?;
+
+            if config.is_sequential(StateTable::Objects) {
+                add_sequential(&mut indexer, &mut registered, LiveObjectsPipeline(matcher.clone()), config.sequential_config()).await?;
+            } else {
+                add_concurrent(&mut indexer, &mut registered, LiveObjectsPipeline(matcher.clone()), config.concurrent_config()).await?;
+            }
+        }
+
+        if indexes(&IndexField::BalanceChanges) {
+            add_concurrent(&mut indexer, &mut registered, BalanceChangesPipeline(matcher.clone()), config.concurrent_config()).await?;
+
+            // Running balances are only correct when committed in order
+            if config.is_sequential(StateTable::Balances) {
+                add_sequential(&mut indexer, &mut registered, BalancesPipeline(matcher.clone()), config.sequential_config()).await?;
+            } else {
+                warn!("Not writing the balances table: it is only kept by a sequential pipeline, \
+                       add `balances` to --sequential to enable it");
+            }
+        }
+
+        if !callbacks.is_empty() {
+            add_concurrent(&mut indexer, &mut registered, CustomIndexPipeline { callbacks }, config.concurrent_config()).await?;
+        }
         
+        // Resolve packages and upgrades in order from the first checkpoint
+        // the framework will ingest
+        registered.retain(|(name, _)| enabled.is_empty() || enabled.iter().any(|p| p.as_str() == *name));
+        let names: Vec<&str> = registered.iter().map(|(name, _)| *name).collect();
+        let start = ingestion_start(&mut conn, &names, first_checkpoint)
+            .await
+            .map_err(IndexerError::Database)?;
+        info!("Resolving package upgrades in order from checkpoint {}", start);
+        matcher.start_at(start);
+
+        // Only needed to set up, so not held while the indexer runs
+        drop(conn);
+
+        // Run the indexer until every pipeline stops. The framework logs a
+        // pipeline's failure and stops that pipeline without reporting it
+        // here, so whether the indexer finished is judged by how far each
+        // pipeline got.
+        let handle = indexer
+            .run()
+            .await
+            .context("Failed to start indexer")
+            .map_err(IndexerError::Ingestion)?;
+
+        handle
+            .await
+            .context("Indexer task panicked")
+            .map_err(IndexerError::Ingestion)?;
+
+        // Without a last checkpoint the pipelines only stop by failing
+        let Some(last_checkpoint) = last_checkpoint else {
+            return Err(IndexerError::Ingestion(anyhow!(
+                "Indexer stopped before reaching last checkpoint"
+            )));
+        };
+
+        let mut conn = AsyncPgConnection::establish(database_url.as_str())
+            .await
+            .context("Failed to connect to database")
+            .map_err(IndexerError::Database)?;
+
+        let watermarks = load_watermarks(&mut conn).await.map_err(IndexerError::Database)?;
+        let behind = pipelines_behind(&watermarks, &registered, last_checkpoint);
+        if !behind.is_empty() {
+            return Err(IndexerError::Ingestion(anyhow!(
+                "Pipelines {} stopped before reaching last checkpoint {}",
+                behind.join(", "),
+                last_checkpoint
+            )));
+        }
+
         Ok(())
     }
 }
 
-// Concrete Pipeline implementation for MyIndexData
-pub struct IndexerPipeline {
-    field_filters: Vec<IndexField>,
-    package_filter: SuiAddress,
-    callbacks: HashMap<IndexField, IndexCallback>,
+// Add `checkpoint_feed` to a `--pipeline` selection that leaves it out. It is
+// the only pipeline that never waits for earlier checkpoints, so without it
+// every pipeline can end up waiting on a checkpoint none of them will submit.
+fn enable_feed(pipelines: &mut Vec<String>) {
+    if !pipelines.is_empty() && !pipelines.iter().any(|p| p == CheckpointFeedPipeline::NAME) {
+        info!("Enabling pipeline {}, which every other pipeline relies on", CheckpointFeedPipeline::NAME);
+        pipelines.push(CheckpointFeedPipeline::NAME.to_string());
+    }
 }
 
-impl IndexerPipeline {
-    fn check_package(&self, package_id: &ObjectID) -> bool {
-        package_id.to_string() == self.package_filter.to_string()
+// Pipelines block their worker thread while waiting for earlier checkpoints,
+// which a current-thread runtime cannot hand over to the rest of the indexer.
+fn check_runtime() -> Result<(), IndexerError> {
+    match tokio::runtime::Handle::try_current() {
+        Ok(handle) if handle.runtime_flavor() == tokio::runtime::RuntimeFlavor::CurrentThread => {
+            Err(IndexerError::Config(anyhow!(
+                "The indexer must run on a multi-threaded tokio runtime"
+            )))
+        }
+        _ => Ok(()),
     }
 }
 
-impl Processor for IndexerPipeline {
-    const NAME: &'static str = "indexer_pipeline";
-    type Value = TransactionWithEffects;
+// Classify a failure to create the cluster by the type of its cause. Database
+// errors raised while connecting, pooling connections or migrating are
+// database failures; anything else comes from the cluster's arguments, such as
+// the ingestion source or metrics address.
+fn cluster_error(e: anyhow::Error) -> IndexerError {
+    let database = e.chain().any(|cause| {
+        cause.is::<diesel::ConnectionError>()
+            || cause.is::<diesel::result::Error>()
+            || cause.is::<diesel_migrations::MigrationError>()
+            || cause.is::<PoolError>()
+            || cause.is::<RunError>()
+    });
 
-    fn process(&self, checkpoint: &Arc<CheckpointData>) -> Result<Vec<Self::Value>> {
-        info!("Processing checkpoint: {}", checkpoint.checkpoint_summary.sequence_number);
-        info!("Target package: {}", self.package_filter);
-        info!("Number of transactions in checkpoint: {}", checkpoint.transactions.len());
-        
-        let mut results = Vec::new();
-        
-        for (i, tx) in checkpoint.transactions.iter().enumerate() {
-            let tx_digest = tx.transaction.digest().to_string();
-            let sender = tx.transaction.transaction_data().sender().to_string();
-            let move_calls = tx.transaction.transaction_data().move_calls();
-            
-            info!("Examining transaction {} of {}: digest={}, sender={}",
-                  i+1, checkpoint.transactions.len(), tx_digest, sender);
-            
-            // Check if any move call in this transaction uses our package
-            if move_calls.is_empty() {
-                info!("  Transaction has no move calls, skipping");
-                continue;
-            }
-            
-            info!("  Transaction has {} move calls", move_calls.len());
-            
-            let mut package_matched = false;
-            let mut matched_calls = Vec::new();
-            
-            for (j, call) in move_calls.iter().enumerate() {
-                let package_id = &call.0;
-                let module_name = &call.1;
-                let function_name = &call.2;
-                
-                info!("  Move call {}: {}::{}::{}", j+1, package_id, module_name, function_name);
-                
-                // Check if this call uses our package
-                if self.check_package(package_id) {
-                    info!("  MATCH FOUND! Transaction {} uses target package in module {}, function {}", 
-                          tx_digest, module_name, function_name);
-                    package_matched = true;
-                    matched_calls.push(serde_json::json!({
-                        "package_id": package_id.to_string(),
-                        "module": module_name,
-                        "function": function_name
-                    }));
+    let e = e.context("Failed to initialize indexer cluster");
+    if database {
+        IndexerError::Database(e)
+    } else {
+        IndexerError::Config(e)
+    }
+}
+
+// Register a concurrent pipeline, naming it in the error if the framework
+// rejects it, and record its name.
+async fn add_concurrent<H>(
+    indexer: &mut IndexerCluster,
+    registered: &mut Vec<(&'static str, u64)>,
+    handler: H,
+    config: ConcurrentConfig,
+) -> Result<(), IndexerError>
+where
+    H: ConcurrentHandler + Send + Sync + 'static,
+{
+    indexer
+        .concurrent_pipeline(handler, config)
+        .await
+        .with_context(|| format!("Failed to register pipeline {}", H::NAME))
+        .map_err(IndexerError::Config)?;
+
+    registered.push((H::NAME, 0));
+    Ok(())
+}
+
+// Register a sequential pipeline, naming it in the error if the framework
+// rejects it, and record its name with the checkpoints it lags behind.
+async fn add_sequential<H>(
+    indexer: &mut IndexerCluster,
+    registered: &mut Vec<(&'static str, u64)>,
+    handler: H,
+    config: SequentialConfig,
+) -> Result<(), IndexerError>
+where
+    H: SequentialHandler + Send + Sync + 'static,
+{
+    let checkpoint_lag = config.checkpoint_lag;
+    indexer
+        .sequential_pipeline(handler, config)
+        .await
+        .with_context(|| format!("Failed to register pipeline {}", H::NAME))
+        .map_err(IndexerError::Config)?;
+
+    registered.push((H::NAME, checkpoint_lag));
+    Ok(())
+}
+
+#[derive(diesel::QueryableByName)]
+struct Watermark {
+    #[diesel(sql_type = diesel::sql_types::Text)]
+    pipeline: String,
+    #[diesel(sql_type = diesel::sql_types::BigInt)]
+    checkpoint_hi_inclusive: i64,
+}
+
+// The first checkpoint the framework ingests: `first_checkpoint` if set,
+// otherwise the checkpoint after the lowest watermark among `pipelines`, where
+// a pipeline without a watermark starts from 0.
+async fn ingestion_start(
+    conn: &mut AsyncPgConnection,
+    pipelines: &[&str],
+    first_checkpoint: Option<u64>,
+) -> Result<u64> {
+    if let Some(first_checkpoint) = first_checkpoint {
+        return Ok(first_checkpoint);
+    }
+
+    let watermarks = load_watermarks(conn).await?;
+    Ok(pipelines
+        .iter()
+        .map(|name| {
+            watermarks
+                .iter()
+                .find(|w| w.pipeline == *name)
+                .map_or(0, |w| w.checkpoint_hi_inclusive as u64 + 1)
+        })
+        .min()
+        .unwrap_or(0))
+}
+
+async fn load_watermarks(conn: &mut AsyncPgConnection) -> Result<Vec<Watermark>> {
+    diesel::sql_query("SELECT pipeline, checkpoint_hi_inclusive FROM watermarks")
+        .load(conn)
+        .await
+        .context("Failed to load watermarks")
+}
+
+// The pipelines whose watermark is missing or short of `last_checkpoint`, less
+// the checkpoints each is registered to lag behind.
+fn pipelines_behind<'a>(
+    watermarks: &[Watermark],
+    pipelines: &[(&'a str, u64)],
+    last_checkpoint: u64,
+) -> Vec<&'a str> {
+    pipelines
+        .iter()
+        .filter(|(name, checkpoint_lag)| {
+            let target = last_checkpoint.saturating_sub(*checkpoint_lag);
+            !watermarks
+                .iter()
+                .any(|w| w.pipeline == *name && w.checkpoint_hi_inclusive as u64 >= target)
+        })
+        .map(|(name, _)| *name)
+        .collect()
+}
+
+// Load the recorded package lineage and return the tracked set extended with
+// every known version descending from it.
+async fn load_package_lineage(
+    conn: &mut AsyncPgConnection,
+    mut packages: BTreeSet<SuiAddress>,
+) -> Result<BTreeSet<SuiAddress>> {
+    use crate::schema::package_lineage;
+
+    let lineage: Vec<PackageLineage> = package_lineage::table
+        .load(conn)
+        .await
+        .context("Failed to load package lineage")?;
+
+    extend_with_lineage(&mut packages, &lineage);
+
+    info!("Tracking {} package versions after loading lineage", packages.len());
+    Ok(packages)
+}
+
+// Add every version in `lineage` that descends from a package in `packages`.
+// Upgrades chain through `previous_package_id`, so repeat until no new
+// versions are added.
+fn extend_with_lineage(packages: &mut BTreeSet<SuiAddress>, lineage: &[PackageLineage]) {
+    loop {
+        let mut added = false;
+        for row in lineage {
+            let tracked = [&row.previous_package_id, &row.original_package_id]
+                .into_iter()
+                .filter_map(|id| id.parse::<SuiAddress>().ok())
+                .any(|id| packages.contains(&id));
+
+            if tracked {
+                if let Ok(id) = row.package_id.parse::<SuiAddress>() {
+                    added |= packages.insert(id);
                 }
             }
-            
-            if !package_matched {
-                info!("  No matching package found in this transaction, skipping");
-                continue;
-            }
+        }
 
-            // Create a structured JSON object for tx_kind
-            let tx_data = tx.transaction.transaction_data();
-            let kind_json = match tx_data.kind() {
-                sui_types::transaction::TransactionKind::ProgrammableTransaction(pt) => {
-                    serde_json::json!({
-                        "type": "ProgrammableTransaction",
-                        "matched_calls": matched_calls,
-                        "total_move_calls": move_calls.len(),
-                        "inputs": pt.inputs,
-                        "commands": pt.commands.iter().map(|cmd| {
-                            match cmd {
-                                Command::MoveCall(call) => {
-                                    serde_json::json!({
-                                        "type": "MoveCall",
-                                        "package": call.package.to_string(),
-                                        "module": call.module.to_string(),
-                                        "function": call.function.to_string(),
-                                    })
-                                },
-                                Command::TransferObjects(_, _) => serde_json::json!({"type": "TransferObjects"}),
-                                Command::SplitCoins(_, _) => serde_json::json!({"type": "SplitCoins"}),
-                                Command::MergeCoins(_, _) => serde_json::json!({"type": "MergeCoins"}),
-                                Command::Publish(_, _) => serde_json::json!({"type": "Publish"}),
-                                Command::MakeMoveVec(_, _) => serde_json::json!({"type": "MakeMoveVec"}),
-                                Command::Upgrade(_, _, _, _) => serde_json::json!({"type": "Upgrade"}),
-                            }
-                        }).collect::<Vec<_>>()
-                    })
-                },
-                other => serde_json::json!({
-                    "type": format!("{:?}", other),
-                    "matched_calls": matched_calls,
-                    "total_move_calls": move_calls.len(),
-                })
-            };
-            
-            // Serialize the full transaction for storage
-            let serialized_tx = serde_json::to_value(&tx.transaction).unwrap_or_default();
-            
-            // Create the transaction record
-            let transaction_record = models::Transaction::new(
-                tx_digest.clone(),
-                checkpoint.checkpoint_summary.sequence_number as i64,
-                sender,
-                kind_json,
-                tx_data.gas_budget() as i64,
-                tx_data.gas_price() as i64,
-                serialized_tx
-            );
-            
-            // Extract transaction effects
-            let effects_json = serde_json::to_value(&tx.effects).unwrap_or_default();
-            let effects_record = TransactionEffect {
-                tx_digest: tx_digest.clone(),
-                effects_json,
-                created_at: None,
-            };
-
-            // Extract transaction events
-            let events_record = tx.events.as_ref().map(|events| TransactionEvent {
-                tx_digest: tx_digest.clone(),
-                events_json: serde_json::to_value(events).unwrap_or_default(),
-                created_at: None,
-            });
-
-            // Extract input objects
-            let input_objects_record = Some(InputObjects {
-                tx_digest: tx_digest.clone(),
-                objects_json: serde_json::to_value(&tx.input_objects).unwrap_or_default(),
-                created_at: None,
-            });
-
-            // Extract output objects
-            let output_objects_record = Some(OutputObjects {
-                tx_digest: tx_digest.clone(),
-                objects_json: serde_json::to_value(&tx.output_objects).unwrap_or_default(),
-                created_at: None,
-            });
-            
-            results.push(TransactionWithEffects {
-                transaction: transaction_record,
-                effects: effects_record,
-                events: events_record,
-                input_objects: input_objects_record,
-                output_objects: output_objects_record,
-            });
+        if !added {
+            break;
         }
-        
-        info!("Finished processing checkpoint {}, found {} matching transactions", 
-              checkpoint.checkpoint_summary.sequence_number, results.len());
-        
-        Ok(results)
     }
 }
 
-#[async_trait::async_trait]
-impl ConcurrentHandler for IndexerPipeline {
-    async fn commit(values: &[Self::Value], conn: &mut db::Connection<'_>) -> Result<usize> {
-        if values.is_empty() {
-            return Ok(0);
-        }
-        
-        // Insert transactions
-        info!("Inserting {} transaction records", values.len());
-        
-        use crate::schema::transactions;
-        
-        let inserted = diesel::insert_into(transactions::table)
-            .values(values.iter().map(|v| &v.transaction).collect::<Vec<_>>())
-            .on_conflict_do_nothing()
-            .execute(conn)
-            .await
-            .map_err(|e| anyhow::anyhow!("Failed to insert transaction records: {}", e))?;
-        
-        info!("Successfully inserted {} transaction records", inserted);
-
-        // Insert transaction effects
-        for value in values {
-            use crate::schema::transaction_effects;
-            
-            diesel::insert_into(transaction_effects::table)
-                .values(&value.effects)
-                .on_conflict_do_nothing()
-                .execute(conn)
-                .await
-                .map_err(|e| anyhow::anyhow!("Failed to insert effects record: {}", e))?;
-
-            // Insert events if present
-            if let Some(events) = &value.events {
-                use crate::schema::transaction_events;
-                diesel::insert_into(transaction_events::table)
-                    .values(events)
-                    .on_conflict_do_nothing()
-                    .execute(conn)
-                    .await
-                    .map_err(|e| anyhow::anyhow!("Failed to insert events record: {}", e))?;
-            }
+// Build the decoder's package cache from the bundled system packages and the
+// packages persisted by previous runs.
+async fn load_packages(conn: &mut AsyncPgConnection) -> Result<PackageCache, IndexerError> {
+    use crate::schema::packages;
 
-            // Insert input objects
-            if let Some(input_objects) = &value.input_objects {
-                use crate::schema::input_objects;
-                diesel::insert_into(input_objects::table)
-                    .values(input_objects)
-                    .on_conflict_do_nothing()
-                    .execute(conn)
-                    .await
-                    .map_err(|e| anyhow::anyhow!("Failed to insert input objects record: {}", e))?;
-            }
+    let cache = PackageCache::with_system_packages().map_err(IndexerError::Decode)?;
+
+    let rows: Vec<Package> = packages::table
+        .load(conn)
+        .await
+        .context("Failed to load packages")
+        .map_err(IndexerError::Database)?;
 
-            // Insert output objects
-            if let Some(output_objects) = &value.output_objects {
-                use crate::schema::output_objects;
-                diesel::insert_into(output_objects::table)
-                    .values(output_objects)
-                    .on_conflict_do_nothing()
-                    .execute(conn)
-                    .await
-                    .map_err(|e| anyhow::anyhow!("Failed to insert output objects record: {}", e))?;
+    // A stored package that cannot be read only loses decoding for its types
+    for row in &rows {
+        let package: MovePackage = match bcs::from_bytes(&row.move_package) {
+            Ok(package) => package,
+            Err(e) => {
+                warn!("Skipping stored package {} for decoding: {}", row.package_id, e);
+                continue;
             }
+        };
+
+        if let Err(e) = cache.insert(&package) {
+            warn!("Skipping stored package {} for decoding: {:#}", row.package_id, e);
         }
-        
-        Ok(inserted)
     }
-}
 
-#[derive(Debug, Clone, FieldCount)]
-pub struct TransactionWithEffects {
-    pub transaction: Transaction,
-    pub effects: TransactionEffect,
-    pub events: Option<TransactionEvent>,
-    pub input_objects: Option<InputObjects>,
-    pub output_objects: Option<OutputObjects>,
+    info!("Loaded {} packages for decoding", rows.len());
+    Ok(cache)
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
 
+    fn address(byte: u8) -> SuiAddress {
+        SuiAddress::from(sui_types::base_types::ObjectID::from_single_byte(byte))
+    }
 
+    fn upgrade(package: u8, original: u8, previous: u8) -> PackageLineage {
+        PackageLineage {
+            package_id: address(package).to_string(),
+            original_package_id: address(original).to_string(),
+            previous_package_id: address(previous).to_string(),
+            version: 0,
+            tx_digest: String::new(),
+            checkpoint_sequence_number: 0,
+            created_at: None,
+        }
+    }
+
+    #[test]
+    fn feed_is_added_to_a_pipeline_selection() {
+        let mut pipelines = vec!["transactions".to_string()];
+        enable_feed(&mut pipelines);
+        assert_eq!(pipelines, ["transactions", CheckpointFeedPipeline::NAME]);
+
+        // Already selected, so not added twice
+        enable_feed(&mut pipelines);
+        assert_eq!(pipelines, ["transactions", CheckpointFeedPipeline::NAME]);
+    }
+
+    #[test]
+    fn feed_is_not_added_without_a_selection() {
+        // No selection already runs every pipeline
+        let mut pipelines = Vec::new();
+        enable_feed(&mut pipelines);
+        assert!(pipelines.is_empty());
+    }
+
+    #[test]
+    fn pipelines_short_of_last_checkpoint_are_behind() {
+        let watermark = |pipeline: &str, checkpoint_hi_inclusive| Watermark {
+            pipeline: pipeline.to_string(),
+            checkpoint_hi_inclusive,
+        };
+        let watermarks = [
+            watermark("transactions", 100),
+            watermark("events", 99),
+            watermark("balances", 90),
+        ];
+
+        assert_eq!(
+            pipelines_behind(&watermarks, &[("transactions", 0), ("events", 0), ("move_calls", 0)], 100),
+            ["events", "move_calls"],
+        );
+        assert!(pipelines_behind(&watermarks, &[("transactions", 0), ("events", 0)], 99).is_empty());
+
+        // A sequential pipeline only has to reach the last checkpoint less its lag
+        assert!(pipelines_behind(&watermarks, &[("balances", 10)], 100).is_empty());
+        assert_eq!(pipelines_behind(&watermarks, &[("balances", 5)], 100), ["balances"]);
+    }
+
+    #[test]
+    fn current_thread_runtime_is_rejected() {
+        let runtime = tokio::runtime::Builder::new_current_thread().build().unwrap();
+        let result = runtime.block_on(async { check_runtime() });
+        assert!(matches!(result, Err(IndexerError::Config(_))));
+    }
+
+    #[test]
+    fn multi_thread_runtime_is_accepted() {
+        let runtime = tokio::runtime::Builder::new_multi_thread().build().unwrap();
+        assert!(runtime.block_on(async { check_runtime() }).is_ok());
+    }
+
+    #[test]
+    fn lineage_follows_chains_in_any_order() {
+        // 1 -> 2 -> 3, listed newest first, with the original of 3 unknown
+        let lineage = [upgrade(3, 9, 2), upgrade(2, 1, 1)];
+        let mut packages = BTreeSet::from([address(1)]);
+
+        extend_with_lineage(&mut packages, &lineage);
+        assert_eq!(packages, BTreeSet::from([address(1), address(2), address(3)]));
+    }
+
+    #[test]
+    fn lineage_follows_original_id() {
+        let lineage = [upgrade(3, 1, 2)];
+        let mut packages = BTreeSet::from([address(1)]);
+
+        extend_with_lineage(&mut packages, &lineage);
+        assert_eq!(packages, BTreeSet::from([address(1), address(3)]));
+    }
+
+    #[test]
+    fn lineage_ignores_untracked_packages() {
+        let lineage = [upgrade(5, 4, 4), upgrade(6, 4, 5)];
+        let mut packages = BTreeSet::from([address(1)]);
+
+        extend_with_lineage(&mut packages, &lineage);
+        assert_eq!(packages, BTreeSet::from([address(1)]));
+    }
+}
@@ -1,3 +1,6 @@
+use std::path::PathBuf;
+
+use anyhow::Context;
 use clap::Parser;
 use sui_indexer_alt_framework::{
     cluster::{self, IndexerCluster}, Result
@@ -8,6 +11,10 @@ use url::Url;
 use sui_indexer_generic::{
     SuiIndexer, 
     IndexField,
+    MatchMode,
+    FilterCombinator,
+    config::PipelineConfig,
+    filter::{Filter, MoveCallFilter},
     models::Transaction
 };
 
@@ -24,8 +31,61 @@ struct Args {
     #[clap(flatten)]
     cluster_args: cluster::Args,
 
+    /// Package to track; repeat the flag or pass a comma-separated list to
+    /// track several packages in one database.
+    #[clap(long, value_delimiter = ',')]
+    package_address: Vec<SuiAddress>,
+
+    /// Only index calls matching `package::module::function`; segments accept
+    /// `*` wildcards, and a package's original ID also matches its upgrades.
+    /// Repeat the flag to allow several entry points.
+    #[clap(long, value_delimiter = ',')]
+    move_call: Vec<MoveCallFilter>,
+
+    /// How transactions are matched: any combination of `move-call`, `event`
+    /// and `object`.
+    #[clap(long, value_delimiter = ',', default_value = "move-call")]
+    match_mode: Vec<MatchMode>,
+
+    /// Index transactions sent by this address. Repeatable.
+    #[clap(long, value_delimiter = ',')]
+    sender: Vec<SuiAddress>,
+
+    /// Index transactions leaving an object owned by this address. Repeatable.
+    #[clap(long, value_delimiter = ',')]
+    owner: Vec<SuiAddress>,
+
+    /// Combine the package and address filters with `and` or `or`.
+    #[clap(long, default_value = "and")]
+    filter_combinator: FilterCombinator,
+
+    /// Filter expression, e.g.
+    /// `(module(0x2::pool) or event(0x2::pool::SwapEvent)) and not failed`.
+    #[clap(long, conflicts_with = "filter_file")]
+    filter: Option<Filter>,
+
+    /// Read the filter expression from a file instead of `--filter`.
+    #[clap(long)]
+    filter_file: Option<PathBuf>,
+
+    /// Drop failed transactions instead of indexing them with a `failure`
+    /// status.
     #[clap(long)]
-    package_address: SuiAddress,
+    skip_failed: bool,
+
+    /// Fields to index, each written to its own tables: any combination of
+    /// `transaction`, `effects`, `events`, `input-objects`, `output-objects`,
+    /// `move-calls`, `object-history` and `balance-changes`. Repeatable.
+    #[clap(long, value_delimiter = ',', default_value = "transaction,effects")]
+    index_field: Vec<IndexField>,
+
+    #[clap(flatten)]
+    pipeline: PipelineConfig,
+
+    /// TOML file with pipeline settings. Flags and environment variables
+    /// take precedence over values in the file.
+    #[clap(long, env = "PIPELINE_CONFIG")]
+    config: Option<PathBuf>,
 }
 
 #[tokio::main]
@@ -36,17 +96,39 @@ async fn main() -> Result<()> {
     
     let mut indexer = SuiIndexer::new();
     
-    // Set the package address to track
-    indexer.set_filter_package(args.package_address);
-    
-    // We want to track both transactions and their effects
-    indexer.set_filter_fields(vec![
-        IndexField::Transaction,
-        IndexField::Effects,
-    ]);
+    // Set the package addresses to track
+    indexer.set_filter_packages(args.package_address);
+    indexer.set_filter_move_calls(args.move_call);
+    indexer.set_match_modes(args.match_mode);
+    indexer.set_filter_senders(args.sender);
+    indexer.set_filter_owners(args.owner);
+    indexer.set_filter_combinator(args.filter_combinator);
+    indexer.set_skip_failed(args.skip_failed);
+
+    let pipeline_config = match &args.config {
+        Some(path) => args.pipeline.or(PipelineConfig::from_file(path)?),
+        None => args.pipeline,
+    };
+    indexer.set_pipeline_config(pipeline_config);
+
+    if let Some(path) = &args.filter_file {
+        let expression = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read filter file {}", path.display()))?;
+        indexer.set_filter(expression.parse()?);
+    } else if let Some(filter) = args.filter {
+        indexer.set_filter(filter);
+    }
+
+    indexer.set_filter_fields(args.index_field);
     
-    // Start indexing with the database URL and cluster args
-    indexer.start(args.database_url, args.cluster_args).await?;
+    // Start indexing with the database URL and cluster args, exiting with a
+    // code identifying the kind of failure
+    if let Err(e) = indexer.start(args.database_url, args.cluster_args).await {
+        let code = e.exit_code();
+        // `{:?}` prints the kind of failure followed by its causes
+        eprintln!("Error: {:?}", anyhow::Error::new(e));
+        std::process::exit(code);
+    }
     
     Ok(())
 } 
\ No newline at end of file
@@ -0,0 +1,467 @@
+use std::collections::BTreeSet;
+use std::sync::{Arc, RwLock};
+use tracing::{debug, info, warn};
+
+use sui_indexer_alt_framework::types::full_checkpoint_content::{CheckpointData, CheckpointTransaction};
+
+use move_core_types::language_storage::{StructTag, TypeTag};
+use sui_types::{
+    base_types::{ObjectID, SuiAddress},
+    effects::TransactionEffectsAPI,
+    object::Owner,
+    transaction::{TransactionDataAPI, Command, TransactionKind},
+};
+
+use crate::decode::MoveDecoder;
+use crate::filter::{Filter, MoveCallFilter};
+use crate::models::{Package, PackageLineage};
+use crate::ordered::InOrder;
+use crate::{FilterCombinator, IndexField, MatchMode};
+
+// Number of checkpoints whose matches are kept after they are resolved, for
+// pipelines further behind
+pub const RETAINED_CHECKPOINTS: u64 = 1000;
+
+// Decides which transactions of a checkpoint are indexed. Every pipeline asks
+// the same matcher about the checkpoints it processes, concurrently and out of
+// order. Package caching and upgrade tracking change what later checkpoints
+// match, so each checkpoint is matched once, in sequence, through `ordered`,
+// and the result is shared by all pipelines.
+pub struct TransactionMatcher {
+    pub(crate) field_filters: Vec<IndexField>,
+    pub(crate) package_filters: RwLock<BTreeSet<SuiAddress>>,
+    pub(crate) call_filters: Vec<MoveCallFilter>,
+    pub(crate) match_modes: Vec<MatchMode>,
+    pub(crate) sender_filters: BTreeSet<SuiAddress>,
+    pub(crate) owner_filters: BTreeSet<SuiAddress>,
+    pub(crate) combinator: FilterCombinator,
+    pub(crate) filter: Option<Filter>,
+    pub(crate) skip_failed: bool,
+    pub(crate) decoder: MoveDecoder,
+    pub(crate) ordered: InOrder<Arc<CheckpointData>, CheckpointMatches>,
+}
+
+// A transaction selected by the filters, with what made it match. It refers
+// to the transaction by its position in the checkpoint, so it can be shared
+// between pipelines without holding on to the checkpoint.
+pub struct MatchedTransaction {
+    pub tx_sequence_in_checkpoint: usize,
+    pub matched_package: Option<ObjectID>,
+    pub matched_calls: Vec<serde_json::Value>,
+    pub matched_events: Vec<serde_json::Value>,
+    pub matched_objects: BTreeSet<ObjectID>,
+}
+
+impl MatchedTransaction {
+    pub fn tx<'a>(&self, checkpoint: &'a CheckpointData) -> &'a CheckpointTransaction {
+        &checkpoint.transactions[self.tx_sequence_in_checkpoint]
+    }
+}
+
+// Everything the matcher found in a checkpoint: the matched transactions, the
+// upgrades of tracked packages and every package published in it.
+#[derive(Default)]
+pub struct CheckpointMatches {
+    pub transactions: Vec<MatchedTransaction>,
+    pub lineage: Vec<PackageLineage>,
+    pub packages: Vec<Package>,
+}
+
+impl TransactionMatcher {
+    pub fn indexes(&self, field: &IndexField) -> bool {
+        self.field_filters.is_empty() || self.field_filters.contains(field)
+    }
+
+    pub fn decoder(&self) -> &MoveDecoder {
+        &self.decoder
+    }
+
+    fn check_package(&self, package_id: &ObjectID) -> bool {
+        self.package_filters
+            .read()
+            .unwrap()
+            .contains(&SuiAddress::from(*package_id))
+    }
+
+    fn has_address_filters(&self) -> bool {
+        !self.sender_filters.is_empty() || !self.owner_filters.is_empty()
+    }
+
+    // Check the sender, and the owners of every object written by the
+    // transaction, against the address filters.
+    fn check_addresses(&self, tx: &CheckpointTransaction) -> bool {
+        let sender = tx.transaction.transaction_data().sender();
+        if self.sender_filters.contains(&sender) {
+            debug!("  MATCH FOUND! Transaction sent by tracked address {}", sender);
+            return true;
+        }
+
+        if self.owner_filters.is_empty() {
+            return false;
+        }
+
+        let changed_owners = tx.effects.all_changed_objects().into_iter().map(|(_, owner, _)| owner);
+        let output_owners = tx.output_objects.iter().map(|o| o.owner.clone());
+
+        for owner in changed_owners.chain(output_owners) {
+            if let Owner::AddressOwner(address) = owner {
+                if self.owner_filters.contains(&address) {
+                    debug!("  MATCH FOUND! Transaction affects tracked address {}", address);
+                    return true;
+                }
+            }
+        }
+
+        false
+    }
+
+    fn combine(
+        &self,
+        package_matched: bool,
+        address_matched: bool,
+        filter_matched: Option<bool>,
+    ) -> bool {
+        let has_packages = !self.package_filters.read().unwrap().is_empty();
+        combine(
+            self.combinator,
+            has_packages.then_some(package_matched),
+            self.has_address_filters().then_some(address_matched),
+            filter_matched,
+        )
+    }
+
+    // An object type matches if it, or any of its type parameters (e.g. the
+    // `T` in `Coin<T>`), is defined in a tracked package. Returns that
+    // package, which for `Coin<T>` is the package defining `T`, not 0x2.
+    fn check_type(&self, tag: &StructTag) -> Option<ObjectID> {
+        let package = ObjectID::from(tag.address);
+        if self.check_package(&package) {
+            return Some(package);
+        }
+
+        tag.type_params.iter().find_map(|param| match param {
+            TypeTag::Struct(inner) => self.check_type(inner),
+            TypeTag::Vector(inner) => match inner.as_ref() {
+                TypeTag::Struct(inner) => self.check_type(inner),
+                _ => None,
+            },
+            _ => None,
+        })
+    }
+
+    // The ID a package was first published at, known for every package
+    // loaded at startup or published since. Unknown packages are their own
+    // original.
+    fn original_id(&self, package_id: &ObjectID) -> ObjectID {
+        self.decoder.cache().original_id(package_id).unwrap_or(*package_id)
+    }
+
+    fn check_call(&self, package_id: &ObjectID, module: &str, function: &str) -> bool {
+        let original_id = self.original_id(package_id);
+        self.call_filters.is_empty()
+            || self.call_filters.iter().any(|f| f.matches(package_id, &original_id, module, function))
+    }
+
+    // Add packages published by this transaction to the decoder's cache, and
+    // return all of them so they are persisted for later runs. Decoding is
+    // best-effort, so a package that cannot be read or serialized is logged
+    // and skipped rather than failing the checkpoint.
+    fn cache_packages(
+        &self,
+        tx: &CheckpointTransaction,
+        checkpoint_sequence_number: i64,
+    ) -> Vec<Package> {
+        let mut packages = Vec::new();
+        for package in tx.output_objects.iter().filter_map(|o| o.data.try_as_package()) {
+            if !self.decoder.cache().contains(&package.id()) {
+                if let Err(e) = self.decoder.cache().insert(package) {
+                    warn!("Skipping package {} for decoding: {:#}", package.id(), e);
+                }
+            }
+
+            let move_package = match bcs::to_bytes(package) {
+                Ok(bytes) => bytes,
+                Err(e) => {
+                    warn!("Failed to serialize package {}, not persisting it: {}", package.id(), e);
+                    continue;
+                }
+            };
+
+            packages.push(Package {
+                package_id: package.id().to_string(),
+                original_package_id: package.original_package_id().to_string(),
+                version: package.version().value() as i64,
+                move_package,
+                checkpoint_sequence_number,
+                created_at: None,
+            });
+        }
+
+        packages
+    }
+
+    // Detect new versions of tracked packages published by this transaction and
+    // add them to the filter. A new version is recognised either by an
+    // `Upgrade` command on a tracked package, or by a published package whose
+    // original ID is tracked. Versions already in the filter are still
+    // returned, as a checkpoint resolved again after its output was dropped
+    // finds its upgrades already there.
+    fn track_upgrades(
+        &self,
+        tx: &CheckpointTransaction,
+        checkpoint_sequence_number: i64,
+    ) -> Vec<PackageLineage> {
+        let upgraded: Vec<ObjectID> = match tx.transaction.transaction_data().kind() {
+            TransactionKind::ProgrammableTransaction(pt) => pt
+                .commands
+                .iter()
+                .filter_map(|cmd| match cmd {
+                    Command::Upgrade(_, _, package_id, _) if self.check_package(package_id) => {
+                        Some(*package_id)
+                    }
+                    _ => None,
+                })
+                .collect(),
+            _ => Vec::new(),
+        };
+
+        let mut lineage = Vec::new();
+        for package in tx.output_objects.iter().filter_map(|o| o.data.try_as_package()) {
+            let package_id = package.id();
+            let original_id = package.original_package_id();
+
+            // A first publish is not an upgrade of anything
+            if package_id == original_id {
+                continue;
+            }
+
+            let previous = if let Some(previous) = upgraded.first() {
+                *previous
+            } else if self.check_package(&original_id) {
+                original_id
+            } else {
+                continue;
+            };
+
+            if self.package_filters.write().unwrap().insert(SuiAddress::from(package_id)) {
+                info!("  Package upgrade detected: {} -> {} (original {})",
+                      previous, package_id, original_id);
+            }
+
+            lineage.push(PackageLineage {
+                package_id: package_id.to_string(),
+                original_package_id: original_id.to_string(),
+                previous_package_id: previous.to_string(),
+                version: package.version().value() as i64,
+                tx_digest: tx.transaction.digest().to_string(),
+                checkpoint_sequence_number,
+                created_at: None,
+            });
+        }
+
+        lineage
+    }
+
+    // Run the match modes, address filters and filter expression over one
+    // transaction.
+    fn match_transaction(
+        &self,
+        tx: &CheckpointTransaction,
+        tx_sequence_in_checkpoint: usize,
+        checkpoint_sequence_number: u64,
+    ) -> Option<MatchedTransaction> {
+        let tx_digest = tx.transaction.digest().to_string();
+
+        if self.skip_failed && !tx.effects.status().is_ok() {
+            debug!("  Transaction {} failed, skipping", tx_digest);
+            return None;
+        }
+
+        let mut matched_package: Option<ObjectID> = None;
+        let mut matched_calls = Vec::new();
+        let mut matched_events = Vec::new();
+
+        // Check if any move call in this transaction uses our package
+        if self.match_modes.contains(&MatchMode::MoveCall) {
+            for (package_id, module_name, function_name) in tx.transaction.transaction_data().move_calls() {
+                if self.check_package(package_id)
+                    && self.check_call(package_id, module_name, function_name)
+                {
+                    debug!("  MATCH FOUND! Transaction {} uses target package in module {}, function {}",
+                           tx_digest, module_name, function_name);
+                    matched_package.get_or_insert(*package_id);
+                    matched_calls.push(serde_json::json!({
+                        "package_id": package_id.to_string(),
+                        "module": module_name,
+                        "function": function_name
+                    }));
+                }
+            }
+        }
+
+        // Check if any input or output object has a type from our package
+        let mut matched_objects = BTreeSet::new();
+        if self.match_modes.contains(&MatchMode::Object) {
+            for object in tx.input_objects.iter().chain(tx.output_objects.iter()) {
+                let Some(tag) = object.struct_tag() else {
+                    continue;
+                };
+
+                if let Some(type_package) = self.check_type(&tag) {
+                    debug!("  MATCH FOUND! Transaction {} touches object {} of type {}",
+                           tx_digest, object.id(), tag);
+                    matched_package.get_or_insert(type_package);
+                    matched_objects.insert(object.id());
+                }
+            }
+        }
+
+        // Check if any emitted event has a type defined in our package
+        if self.match_modes.contains(&MatchMode::Event) {
+            for (j, event) in tx.events.iter().flat_map(|e| e.data.iter()).enumerate() {
+                let type_package = ObjectID::from(event.type_.address);
+                if !self.check_package(&type_package) {
+                    continue;
+                }
+
+                debug!("  MATCH FOUND! Transaction {} emitted event {}", tx_digest, event.type_);
+                matched_package.get_or_insert(type_package);
+                matched_events.push(serde_json::json!({
+                    "event_seq": j,
+                    "type": event.type_.to_string(),
+                    "package_id": event.package_id.to_string(),
+                    "module": event.transaction_module.to_string(),
+                }));
+            }
+        }
+
+        let address_matched = self.check_addresses(tx);
+        let filter_matched = self
+            .filter
+            .as_ref()
+            .map(|f| f.matches(tx, checkpoint_sequence_number, &|id| self.original_id(id)));
+        if !self.combine(matched_package.is_some(), address_matched, filter_matched) {
+            return None;
+        }
+
+        Some(MatchedTransaction {
+            tx_sequence_in_checkpoint,
+            matched_package,
+            matched_calls,
+            matched_events,
+            matched_objects,
+        })
+    }
+
+    // Match one checkpoint, caching its packages and following its upgrades
+    // first. Must run in checkpoint order.
+    fn resolve_checkpoint(&self, checkpoint: &CheckpointData) -> CheckpointMatches {
+        let sequence_number = checkpoint.checkpoint_summary.sequence_number;
+        debug!("Matching {} transactions in checkpoint {}", checkpoint.transactions.len(), sequence_number);
+
+        let mut matches = CheckpointMatches::default();
+        for (i, tx) in checkpoint.transactions.iter().enumerate() {
+            // Cache newly published packages so their types can be decoded
+            matches.packages.extend(self.cache_packages(tx, sequence_number as i64));
+
+            // Follow upgrades of tracked packages before matching, so calls to
+            // the new version later in this checkpoint are picked up
+            matches.lineage.extend(self.track_upgrades(tx, sequence_number as i64));
+
+            if let Some(matched) = self.match_transaction(tx, i, sequence_number) {
+                matches.transactions.push(matched);
+            }
+        }
+
+        matches
+    }
+
+    // Set the first checkpoint resolved in order, i.e. the first checkpoint
+    // the indexer ingests.
+    pub fn start_at(&self, checkpoint: u64) {
+        self.ordered.start_at(checkpoint);
+    }
+
+    // Hand a checkpoint to the in-order stage without waiting for it.
+    pub fn feed(&self, checkpoint: &Arc<CheckpointData>) {
+        let sequence_number = checkpoint.checkpoint_summary.sequence_number;
+        self.ordered.submit(sequence_number, checkpoint.clone(), |c| self.resolve_checkpoint(c));
+    }
+
+    // What the matcher found in a checkpoint, once every checkpoint before it
+    // has been resolved. Each checkpoint is resolved once for all pipelines.
+    pub fn match_checkpoint(&self, checkpoint: &Arc<CheckpointData>) -> Arc<CheckpointMatches> {
+        let sequence_number = checkpoint.checkpoint_summary.sequence_number;
+        self.ordered.resolve(sequence_number, checkpoint.clone(), |c| self.resolve_checkpoint(c))
+    }
+}
+
+// Combine the outcome of each kind of filter, `None` where that kind is not
+// set. The package and address filters are combined with `combinator` when
+// both are set, and the filter expression is ANDed with the result. Nothing
+// matches when no filter is set.
+fn combine(
+    combinator: FilterCombinator,
+    package_matched: Option<bool>,
+    address_matched: Option<bool>,
+    filter_matched: Option<bool>,
+) -> bool {
+    let matched = match (package_matched, address_matched) {
+        (Some(package_matched), Some(address_matched)) => Some(match combinator {
+            FilterCombinator::And => package_matched && address_matched,
+            FilterCombinator::Or => package_matched || address_matched,
+        }),
+        (package_matched, None) => package_matched,
+        (None, address_matched) => address_matched,
+    };
+
+    match (matched, filter_matched) {
+        (Some(matched), Some(filter_matched)) => matched && filter_matched,
+        (Some(matched), None) => matched,
+        (None, Some(filter_matched)) => filter_matched,
+        (None, None) => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use FilterCombinator::{And, Or};
+
+    #[test]
+    fn combinator_applies_when_both_kinds_are_set() {
+        assert!(combine(And, Some(true), Some(true), None));
+        assert!(!combine(And, Some(true), Some(false), None));
+        assert!(combine(Or, Some(false), Some(true), None));
+        assert!(!combine(Or, Some(false), Some(false), None));
+    }
+
+    #[test]
+    fn single_kind_decides_alone() {
+        for combinator in [And, Or] {
+            assert!(combine(combinator, Some(true), None, None));
+            assert!(!combine(combinator, Some(false), None, None));
+            assert!(combine(combinator, None, Some(true), None));
+            assert!(!combine(combinator, None, Some(false), None));
+        }
+    }
+
+    #[test]
+    fn filter_expression_is_anded() {
+        assert!(combine(Or, Some(false), Some(true), Some(true)));
+        assert!(!combine(Or, Some(true), Some(true), Some(false)));
+        assert!(!combine(And, Some(true), None, Some(false)));
+    }
+
+    #[test]
+    fn filter_expression_decides_alone() {
+        assert!(combine(And, None, None, Some(true)));
+        assert!(!combine(And, None, None, Some(false)));
+    }
+
+    #[test]
+    fn nothing_matches_without_filters() {
+        assert!(!combine(And, None, None, None));
+        assert!(!combine(Or, None, None, None));
+    }
+}
@@ -6,6 +6,7 @@ use diesel::pg::Pg;
 use serde::{Deserialize, Serialize};
 use sui_indexer_alt_framework::FieldCount;
 use chrono::NaiveDateTime;
+use bigdecimal::BigDecimal;
 
 use serde_json::Value;
 
@@ -27,6 +28,17 @@ pub struct Transaction {
     pub gas_price: i64,
     pub serialized_tx: Value,
     pub created_at: Option<NaiveDateTime>,
+    pub matched_package: Option<String>,
+    pub matched_object_ids: Option<Vec<String>>,
+    pub status: String,
+    pub error: Option<String>,
+    pub gas_owner: String,
+    pub computation_cost: i64,
+    pub storage_cost: i64,
+    pub storage_rebate: i64,
+    pub non_refundable_storage_fee: i64,
+    pub timestamp_ms: i64,
+    pub epoch: i64,
 }
 
 #[derive(Debug, Clone, Queryable, Insertable, Serialize, Deserialize, FieldCount)]
@@ -34,11 +46,13 @@ pub struct Transaction {
 pub struct CheckpointTransaction {
     pub tx_digest: String,
     pub transaction_digest: String,
-    pub transaction_effects_digest: Option<String>,
+    pub transaction_effects_digest: String,
     pub transaction_events_digest: Option<String>,
-    pub input_objects_digest: Option<String>,
-    pub output_objects_digest: Option<String>,
     pub created_at: Option<NaiveDateTime>,
+    pub timestamp_ms: i64,
+    pub epoch: i64,
+    pub checkpoint_sequence_number: i64,
+    pub tx_sequence_in_checkpoint: i64,
 }
 
 #[derive(Debug, Clone, Queryable, Insertable, Serialize, Deserialize, FieldCount)]
@@ -47,6 +61,8 @@ pub struct TransactionEffect {
     pub tx_digest: String,
     pub effects_json: Value,
     pub created_at: Option<NaiveDateTime>,
+    pub timestamp_ms: i64,
+    pub epoch: i64,
 }
 
 #[derive(Debug, Clone, Queryable, Insertable, Serialize, Deserialize, FieldCount)]
@@ -55,6 +71,8 @@ pub struct TransactionEvent {
     pub tx_digest: String,
     pub events_json: Value,
     pub created_at: Option<NaiveDateTime>,
+    pub timestamp_ms: i64,
+    pub epoch: i64,
 }
 
 #[derive(Debug, Clone, Queryable, Insertable, Serialize, Deserialize, FieldCount)]
@@ -63,6 +81,8 @@ pub struct InputObjects {
     pub tx_digest: String,
     pub objects_json: Value,
     pub created_at: Option<NaiveDateTime>,
+    pub timestamp_ms: i64,
+    pub epoch: i64,
 }
 
 #[derive(Debug, Clone, Queryable, Insertable, Serialize, Deserialize, FieldCount)]
@@ -71,17 +91,199 @@ pub struct OutputObjects {
     pub tx_digest: String,
     pub objects_json: Value,
     pub created_at: Option<NaiveDateTime>,
+    pub timestamp_ms: i64,
+    pub epoch: i64,
+}
+
+#[derive(Debug, Clone, Queryable, Insertable, Serialize, Deserialize, FieldCount)]
+#[diesel(table_name = crate::schema::events)]
+pub struct Event {
+    pub tx_digest: String,
+    pub event_seq: i64,
+    pub package_id: String,
+    pub module: String,
+    pub event_type: String,
+    pub sender: String,
+    pub contents: Vec<u8>,
+    pub checkpoint_sequence_number: i64,
+    pub created_at: Option<NaiveDateTime>,
+    pub parsed_json: Option<Value>,
+    pub timestamp_ms: i64,
+    pub epoch: i64,
+}
+
+#[derive(Debug, Clone, Queryable, Insertable, Serialize, Deserialize, FieldCount)]
+#[diesel(table_name = crate::schema::move_calls)]
+pub struct MoveCall {
+    pub tx_digest: String,
+    pub command_index: i32,
+    pub package: String,
+    pub module: String,
+    pub function: String,
+    pub type_arguments: Value,
+    pub arguments: Value,
+    pub checkpoint_sequence_number: i64,
+    pub created_at: Option<NaiveDateTime>,
+    pub timestamp_ms: i64,
+    pub epoch: i64,
+}
+
+#[derive(Debug, Clone, Queryable, Insertable, Serialize, Deserialize, FieldCount)]
+#[diesel(table_name = crate::schema::package_lineage)]
+pub struct PackageLineage {
+    pub package_id: String,
+    pub original_package_id: String,
+    pub previous_package_id: String,
+    pub version: i64,
+    pub tx_digest: String,
+    pub checkpoint_sequence_number: i64,
+    pub created_at: Option<NaiveDateTime>,
+}
+
+#[derive(Debug, Clone, Queryable, Insertable, Serialize, Deserialize, FieldCount)]
+#[diesel(table_name = crate::schema::packages)]
+pub struct Package {
+    pub package_id: String,
+    pub original_package_id: String,
+    pub version: i64,
+    pub move_package: Vec<u8>,
+    pub checkpoint_sequence_number: i64,
+    pub created_at: Option<NaiveDateTime>,
+}
+
+#[derive(Debug, Clone, Queryable, Insertable, Serialize, Deserialize, FieldCount)]
+#[diesel(table_name = crate::schema::transaction_objects)]
+pub struct TransactionObject {
+    pub tx_digest: String,
+    pub object_id: String,
+    pub version: i64,
+    pub kind: String,
+    pub object_type: Option<String>,
+    pub owner_kind: String,
+    pub owner_address: Option<String>,
+    pub parsed_json: Option<Value>,
+    pub checkpoint_sequence_number: i64,
+    pub created_at: Option<NaiveDateTime>,
+    pub timestamp_ms: i64,
+    pub epoch: i64,
+}
+
+#[derive(Debug, Clone, Queryable, Insertable, Serialize, Deserialize, FieldCount)]
+#[diesel(table_name = crate::schema::objects_history)]
+pub struct ObjectHistory {
+    pub object_id: String,
+    pub version: i64,
+    pub digest: String,
+    pub object_type: Option<String>,
+    pub owner_kind: Option<String>,
+    pub owner_address: Option<String>,
+    pub tx_digest: String,
+    pub checkpoint_sequence_number: i64,
+    pub deleted: bool,
+    pub created_at: Option<NaiveDateTime>,
+    pub timestamp_ms: i64,
+    pub epoch: i64,
+}
+
+#[derive(Debug, Clone, Queryable, Insertable, Serialize, Deserialize, FieldCount)]
+#[diesel(table_name = crate::schema::objects)]
+pub struct LiveObject {
+    pub object_id: String,
+    pub version: i64,
+    pub digest: String,
+    pub object_type: Option<String>,
+    pub owner_kind: Option<String>,
+    pub owner_address: Option<String>,
+    pub tx_digest: String,
+    pub checkpoint_sequence_number: i64,
+    pub deleted: bool,
+    pub created_at: Option<NaiveDateTime>,
+    pub timestamp_ms: i64,
+    pub epoch: i64,
+}
+
+impl From<ObjectHistory> for LiveObject {
+    fn from(history: ObjectHistory) -> Self {
+        Self {
+            object_id: history.object_id,
+            version: history.version,
+            digest: history.digest,
+            object_type: history.object_type,
+            owner_kind: history.owner_kind,
+            owner_address: history.owner_address,
+            tx_digest: history.tx_digest,
+            checkpoint_sequence_number: history.checkpoint_sequence_number,
+            deleted: history.deleted,
+            created_at: None,
+            timestamp_ms: history.timestamp_ms,
+            epoch: history.epoch,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Queryable, Insertable, Serialize, Deserialize, FieldCount)]
+#[diesel(table_name = crate::schema::balance_changes)]
+pub struct BalanceChange {
+    pub tx_digest: String,
+    pub owner: String,
+    pub coin_type: String,
+    pub amount: BigDecimal,
+    pub checkpoint_sequence_number: i64,
+    pub created_at: Option<NaiveDateTime>,
+    pub timestamp_ms: i64,
+    pub epoch: i64,
+}
+
+#[derive(Debug, Clone, Queryable, Insertable, Serialize, Deserialize, FieldCount)]
+#[diesel(table_name = crate::schema::balances)]
+pub struct Balance {
+    pub owner: String,
+    pub coin_type: String,
+    pub balance: BigDecimal,
+    pub checkpoint_sequence_number: i64,
+    pub timestamp_ms: i64,
+    pub epoch: i64,
+    pub created_at: Option<NaiveDateTime>,
+}
+
+#[derive(Debug, Clone, Queryable, Insertable, Serialize, Deserialize, FieldCount)]
+#[diesel(table_name = crate::schema::checkpoints)]
+pub struct Checkpoint {
+    pub sequence_number: i64,
+    pub digest: String,
+    pub epoch: i64,
+    pub timestamp_ms: i64,
+    pub network_total_transactions: i64,
+    pub transaction_count: i64,
+    pub matched_transactions: i64,
+    pub computation_cost: i64,
+    pub storage_cost: i64,
+    pub storage_rebate: i64,
+    pub non_refundable_storage_fee: i64,
+    pub created_at: Option<NaiveDateTime>,
 }
 
 impl Transaction {
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         tx_digest: String,
         checkpoint_sequence_number: i64,
+        timestamp_ms: i64,
+        epoch: i64,
         sender: String,
         tx_kind: Value,
         gas_budget: i64,
         gas_price: i64,
         serialized_tx: Value,
+        matched_package: Option<String>,
+        matched_object_ids: Option<Vec<String>>,
+        status: String,
+        error: Option<String>,
+        gas_owner: String,
+        computation_cost: i64,
+        storage_cost: i64,
+        storage_rebate: i64,
+        non_refundable_storage_fee: i64,
     ) -> Self {
         Self {
             tx_digest,
@@ -92,6 +294,17 @@ impl Transaction {
             gas_price,
             serialized_tx,
             created_at: None,
+            matched_package,
+            matched_object_ids,
+            status,
+            error,
+            gas_owner,
+            computation_cost,
+            storage_cost,
+            storage_rebate,
+            non_refundable_storage_fee,
+            timestamp_ms,
+            epoch,
         }
     }
 }
@@ -99,15 +312,23 @@ impl Transaction {
 impl CheckpointTransaction {
     pub fn new(
         tx_digest: String,
+        transaction_effects_digest: String,
+        transaction_events_digest: Option<String>,
+        checkpoint_sequence_number: i64,
+        tx_sequence_in_checkpoint: i64,
+        timestamp_ms: i64,
+        epoch: i64,
     ) -> Self {
         Self {
             tx_digest: tx_digest.clone(),
             transaction_digest: tx_digest,
-            transaction_effects_digest: None,
-            transaction_events_digest: None,
-            input_objects_digest: None,
-            output_objects_digest: None,
+            transaction_effects_digest,
+            transaction_events_digest,
             created_at: None,
+            timestamp_ms,
+            epoch,
+            checkpoint_sequence_number,
+            tx_sequence_in_checkpoint,
         }
     }
 }
@@ -0,0 +1,311 @@
+use std::collections::BTreeMap;
+use std::sync::{Arc, Condvar, Mutex, MutexGuard, PoisonError};
+use std::thread;
+
+// Runs a stateful step over checkpoints strictly in sequence order, although
+// the pipelines calling it process checkpoints concurrently and out of order.
+// A caller submits its checkpoint and waits until every earlier checkpoint has
+// been through the step, so the step always sees the state left by the one
+// before. All pipelines submit the same checkpoints: each is stepped once, and
+// its output is kept for a while for the pipelines that get to it later.
+//
+// Waiting blocks the calling thread through `tokio::task::block_in_place`, so
+// callers inside a tokio runtime must run on a multi-threaded one.
+pub struct InOrder<I, O> {
+    state: Mutex<State<I, O>>,
+    advanced: Condvar,
+    retain: u64,
+}
+
+struct State<I, O> {
+    // Next sequence number to step
+    next: u64,
+    // Whether a caller is stepping queued inputs, which it does unlocked
+    stepping: bool,
+    // Whether a step panicked, so later sequence numbers will never be stepped
+    failed: bool,
+    // Submitted inputs waiting for an earlier sequence number
+    pending: BTreeMap<u64, I>,
+    // Outputs of the last `retain` sequence numbers
+    resolved: BTreeMap<u64, Arc<O>>,
+}
+
+impl<I: Clone, O> InOrder<I, O> {
+    pub fn new(next: u64, retain: u64) -> Self {
+        Self {
+            state: Mutex::new(State {
+                next,
+                stepping: false,
+                failed: false,
+                pending: BTreeMap::new(),
+                resolved: BTreeMap::new(),
+            }),
+            advanced: Condvar::new(),
+            retain,
+        }
+    }
+
+    // Set the first sequence number to step. Must be called before anything
+    // is submitted.
+    pub fn start_at(&self, next: u64) {
+        self.state.lock().unwrap().next = next;
+    }
+
+    // Submit `input` for `seq` without waiting for its output.
+    pub fn submit(&self, seq: u64, input: I, step: impl Fn(&I) -> O) {
+        let state = self.state.lock().unwrap();
+        drop(self.advance(state, seq, input, &step));
+    }
+
+    // Submit `input` for `seq` and wait for its output. A sequence number
+    // before the starting point, or stepped so long ago that its output was
+    // dropped, is stepped again on the spot: the state has only grown since.
+    pub fn resolve(&self, seq: u64, input: I, step: impl Fn(&I) -> O) -> Arc<O> {
+        let mut state = self.state.lock().unwrap();
+        if let Some(output) = state.resolved.get(&seq) {
+            return output.clone();
+        }
+
+        if seq >= state.next {
+            state = self.advance(state, seq, input.clone(), &step);
+
+            // Runs on a tokio worker, which must be handed back while blocked
+            state = tokio::task::block_in_place(|| {
+                self.advanced
+                    .wait_while(state, |s| seq >= s.next && !s.failed)
+                    .unwrap()
+            });
+
+            if state.failed {
+                panic!("Stepping a sequence number before {} panicked", seq);
+            }
+
+            if let Some(output) = state.resolved.get(&seq) {
+                return output.clone();
+            }
+        }
+
+        drop(state);
+        Arc::new(step(&input))
+    }
+
+    // Queue `input` and, unless another caller already is, step every queued
+    // input that is next in sequence. The lock is released while stepping, so
+    // callers reading outputs or queueing inputs are not held up by the step.
+    fn advance<'a>(
+        &'a self,
+        mut state: MutexGuard<'a, State<I, O>>,
+        seq: u64,
+        input: I,
+        step: &impl Fn(&I) -> O,
+    ) -> MutexGuard<'a, State<I, O>> {
+        if seq >= state.next {
+            state.pending.entry(seq).or_insert(input);
+        }
+
+        if state.stepping {
+            return state;
+        }
+
+        state.stepping = true;
+        let _failed = FailOnPanic(self);
+        loop {
+            let next = state.next;
+            let Some(input) = state.pending.remove(&next) else {
+                break;
+            };
+
+            drop(state);
+            let output = Arc::new(step(&input));
+            state = self.state.lock().unwrap();
+
+            state.resolved.insert(next, output);
+            state.next += 1;
+
+            let oldest = state.next.saturating_sub(self.retain);
+            state.resolved = state.resolved.split_off(&oldest);
+            self.advanced.notify_all();
+        }
+
+        state.stepping = false;
+        state
+    }
+}
+
+// Marks the stage failed if the step panics, waking every waiting caller
+// rather than leaving them blocked on a sequence number that never comes.
+struct FailOnPanic<'a, I, O>(&'a InOrder<I, O>);
+
+impl<I, O> Drop for FailOnPanic<'_, I, O> {
+    fn drop(&mut self) {
+        if thread::panicking() {
+            let mut state = self.0.state.lock().unwrap_or_else(PoisonError::into_inner);
+            state.failed = true;
+            self.0.advanced.notify_all();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeSet;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::mpsc;
+    use std::thread;
+    use std::time::Duration;
+
+    // A package upgrade, or a call to a package, against a tracked set that
+    // upgrades of tracked packages extend.
+    #[derive(Clone)]
+    enum Tx {
+        Upgrade { from: u32, to: u32 },
+        Call(u32),
+    }
+
+    fn step(tracked: &Mutex<BTreeSet<u32>>) -> impl Fn(&Tx) -> bool + Copy + '_ {
+        move |tx| {
+            let mut tracked = tracked.lock().unwrap();
+            match tx {
+                Tx::Upgrade { from, to } => {
+                    if tracked.contains(from) {
+                        tracked.insert(*to);
+                    }
+                    false
+                }
+                Tx::Call(package) => tracked.contains(package),
+            }
+        }
+    }
+
+    fn wait_pending<I: Clone, O>(stage: &InOrder<I, O>, seq: u64) {
+        while !stage.state.lock().unwrap().pending.contains_key(&seq) {
+            thread::sleep(Duration::from_millis(1));
+        }
+    }
+
+    #[test]
+    fn call_after_upgrade_matches_when_processed_first() {
+        let tracked = Mutex::new(BTreeSet::from([1]));
+        let step = step(&tracked);
+        let stage = InOrder::new(10, 100);
+
+        thread::scope(|s| {
+            // The call to the new version in checkpoint 11 is processed first
+            let call = s.spawn(|| stage.resolve(11, Tx::Call(2), step));
+            wait_pending(&stage, 11);
+
+            // and must wait for the upgrade in checkpoint 10
+            let upgrade = stage.resolve(10, Tx::Upgrade { from: 1, to: 2 }, step);
+            assert!(!*upgrade);
+            assert!(*call.join().unwrap());
+        });
+    }
+
+    #[test]
+    fn each_checkpoint_is_stepped_once() {
+        let steps = AtomicUsize::new(0);
+        let step = |seq: &u64| {
+            steps.fetch_add(1, Ordering::SeqCst);
+            *seq
+        };
+        let stage = &InOrder::new(0, 100);
+
+        thread::scope(|s| {
+            let handles: Vec<_> = (0..4)
+                .flat_map(|_| (0..5u64).rev())
+                .map(|seq| s.spawn(move || *stage.resolve(seq, seq, step)))
+                .collect();
+
+            for (handle, seq) in handles.into_iter().zip((0..4).flat_map(|_| (0..5u64).rev())) {
+                assert_eq!(handle.join().unwrap(), seq);
+            }
+        });
+
+        assert_eq!(steps.load(Ordering::SeqCst), 5);
+    }
+
+    #[test]
+    fn submit_unblocks_waiting_resolve() {
+        let tracked = Mutex::new(BTreeSet::from([1]));
+        let step = step(&tracked);
+        let stage = InOrder::new(0, 100);
+
+        thread::scope(|s| {
+            let call = s.spawn(|| stage.resolve(1, Tx::Call(2), step));
+            wait_pending(&stage, 1);
+
+            stage.submit(0, Tx::Upgrade { from: 1, to: 2 }, step);
+            assert!(*call.join().unwrap());
+        });
+    }
+
+    #[test]
+    fn checkpoints_before_start_do_not_wait() {
+        let tracked = Mutex::new(BTreeSet::from([1, 2]));
+        let stage = InOrder::new(10, 100);
+
+        assert!(*stage.resolve(3, Tx::Call(2), step(&tracked)));
+        assert_eq!(stage.state.lock().unwrap().next, 10);
+    }
+
+    #[test]
+    fn outputs_are_read_while_stepping() {
+        let (release, released) = mpsc::channel::<()>();
+        let released = Mutex::new(released);
+        let step = |seq: &u64| {
+            if *seq == 1 {
+                released.lock().unwrap().recv().unwrap();
+            }
+            *seq
+        };
+        let stage = InOrder::new(0, 100);
+        stage.submit(0, 0, step);
+
+        thread::scope(|s| {
+            let slow = s.spawn(|| *stage.resolve(1, 1, step));
+            while !stage.state.lock().unwrap().stepping {
+                thread::sleep(Duration::from_millis(1));
+            }
+
+            // Checkpoint 1 is being stepped, without holding up checkpoint 0
+            // or the queueing of checkpoint 2
+            assert_eq!(*stage.resolve(0, 0, step), 0);
+            stage.submit(2, 2, step);
+
+            release.send(()).unwrap();
+            assert_eq!(slow.join().unwrap(), 1);
+        });
+
+        assert_eq!(stage.state.lock().unwrap().next, 3);
+    }
+
+    #[test]
+    fn panicking_step_wakes_waiting_resolve() {
+        let step = |seq: &u64| {
+            assert_ne!(*seq, 0, "step failed");
+            *seq
+        };
+        let stage = InOrder::new(0, 100);
+
+        thread::scope(|s| {
+            let waiting = s.spawn(|| *stage.resolve(1, 1, step));
+            wait_pending(&stage, 1);
+
+            assert!(s.spawn(|| stage.submit(0, 0, step)).join().is_err());
+            assert!(waiting.join().is_err());
+        });
+    }
+
+    #[test]
+    fn old_outputs_are_dropped() {
+        let stage = InOrder::new(0, 2);
+        for seq in 0..5u64 {
+            stage.submit(seq, seq, |seq| *seq);
+        }
+
+        let state = stage.state.lock().unwrap();
+        assert_eq!(state.next, 5);
+        assert_eq!(state.resolved.keys().copied().collect::<Vec<_>>(), vec![3, 4]);
+    }
+}
@@ -0,0 +1,917 @@
+use std::collections::{BTreeMap, HashMap};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use anyhow::Context;
+use bigdecimal::BigDecimal;
+use diesel::prelude::*;
+use diesel_async::{AsyncPgConnection, RunQueryDsl};
+use tracing::info;
+
+use sui_indexer_alt_framework::{
+    db,
+    pipeline::{
+        concurrent::Handler as ConcurrentHandler,
+        sequential::Handler as SequentialHandler,
+        Processor,
+    },
+    types::full_checkpoint_content::CheckpointData,
+    FieldCount,
+    Result,
+};
+
+use move_core_types::language_storage::TypeTag;
+use sui_types::{
+    effects::TransactionEffectsAPI,
+    execution_status::ExecutionStatus,
+    message_envelope::Message,
+    object::{Object, Owner},
+    transaction::{TransactionDataAPI, Command, TransactionKind},
+};
+
+use crate::matcher::{MatchedTransaction, TransactionMatcher};
+use crate::models::{
+    self, Balance, BalanceChange, Checkpoint, Event, InputObjects, LiveObject, MoveCall, MyIndexData,
+    ObjectHistory, OutputObjects, Package, PackageLineage, Transaction, TransactionEffect,
+    TransactionEvent, TransactionObject,
+};
+use crate::{IndexCallback, IndexField};
+
+// Postgres rejects statements with more than 65535 bind parameters
+pub const MAX_BIND_PARAMS: usize = u16::MAX as usize;
+
+// Cap on rows per INSERT from `PipelineConfig::max_rows_per_insert`, 0 when
+// unset. Commits are static functions, so it is shared by the whole process
+// and set by the most recent `SuiIndexer::start`.
+static MAX_ROWS_PER_INSERT: AtomicUsize = AtomicUsize::new(0);
+
+pub(crate) fn set_max_rows_per_insert(rows: Option<usize>) {
+    MAX_ROWS_PER_INSERT.store(rows.map_or(0, |rows| rows.max(1)), Ordering::Relaxed);
+}
+
+// Number of rows of `T` written in a single multi-row INSERT.
+pub fn rows_per_insert<T: FieldCount>() -> usize {
+    let limit = MAX_BIND_PARAMS / T::FIELD_COUNT;
+    match MAX_ROWS_PER_INSERT.load(Ordering::Relaxed) {
+        0 => limit,
+        rows => rows.min(limit),
+    }
+}
+
+// Insert `$rows` (a `Vec<&Model>`) into `$table` as multi-row INSERTs chunked
+// to stay under the bind parameter limit, ignoring rows that already exist.
+// Evaluates to the number of rows inserted.
+macro_rules! insert_chunked {
+    ($conn:expr, $table:path, $model:ty, $rows:expr, $what:literal) => {{
+        let mut inserted = 0;
+        for chunk in $rows.chunks(rows_per_insert::<$model>()) {
+            inserted += diesel::insert_into($table)
+                .values(chunk.to_vec())
+                .on_conflict_do_nothing()
+                .execute($conn)
+                .await
+                .map_err(|e| anyhow::anyhow!(concat!("Failed to insert ", $what, ": {}"), e))?;
+        }
+        inserted
+    }};
+}
+
+// Declare a pipeline writing the `$model` rows that `$extract` derives from
+// each matched transaction into `$table`. Each pipeline has its own watermark,
+// named after the table it writes.
+macro_rules! transaction_pipeline {
+    ($pipeline:ident, $name:literal, $table:path, $model:ty, $extract:path) => {
+        pub struct $pipeline(pub Arc<TransactionMatcher>);
+
+        impl Processor for $pipeline {
+            const NAME: &'static str = $name;
+            type Value = $model;
+
+            fn process(&self, checkpoint: &Arc<CheckpointData>) -> Result<Vec<Self::Value>> {
+                let matches = self.0.match_checkpoint(checkpoint);
+                Ok(matches
+                    .transactions
+                    .iter()
+                    .flat_map(|matched| $extract(&self.0, checkpoint, matched))
+                    .collect())
+            }
+        }
+
+        impl $pipeline {
+            // Write `values` exactly as `commit` does, on any connection
+            pub async fn write(values: &[$model], conn: &mut AsyncPgConnection) -> Result<usize> {
+                let rows: Vec<&$model> = values.iter().collect();
+                Ok(insert_chunked!(conn, $table, $model, rows, $name))
+            }
+        }
+
+        #[async_trait::async_trait]
+        impl ConcurrentHandler for $pipeline {
+            async fn commit(values: &[Self::Value], conn: &mut db::Connection<'_>) -> Result<usize> {
+                Self::write(values, conn).await
+            }
+        }
+    };
+}
+
+transaction_pipeline!(
+    TransactionsPipeline, "transactions",
+    crate::schema::transactions::table, Transaction, transaction_row
+);
+transaction_pipeline!(
+    CheckpointTransactionsPipeline, "checkpoint_transactions",
+    crate::schema::checkpoint_transactions::table, models::CheckpointTransaction, checkpoint_transaction_row
+);
+transaction_pipeline!(
+    EffectsPipeline, "transaction_effects",
+    crate::schema::transaction_effects::table, TransactionEffect, effects_row
+);
+transaction_pipeline!(
+    TransactionEventsPipeline, "transaction_events",
+    crate::schema::transaction_events::table, TransactionEvent, transaction_events_row
+);
+transaction_pipeline!(
+    EventsPipeline, "events",
+    crate::schema::events::table, Event, event_rows
+);
+transaction_pipeline!(
+    InputObjectsPipeline, "input_objects",
+    crate::schema::input_objects::table, InputObjects, input_objects_row
+);
+transaction_pipeline!(
+    OutputObjectsPipeline, "output_objects",
+    crate::schema::output_objects::table, OutputObjects, output_objects_row
+);
+transaction_pipeline!(
+    TransactionObjectsPipeline, "transaction_objects",
+    crate::schema::transaction_objects::table, TransactionObject, transaction_object_rows
+);
+transaction_pipeline!(
+    MoveCallsPipeline, "move_calls",
+    crate::schema::move_calls::table, MoveCall, move_call_rows
+);
+transaction_pipeline!(
+    ObjectsHistoryPipeline, "objects_history",
+    crate::schema::objects_history::table, ObjectHistory, object_history_rows
+);
+transaction_pipeline!(
+    BalanceChangesPipeline, "balance_changes",
+    crate::schema::balance_changes::table, BalanceChange, balance_change_rows
+);
+
+fn transaction_row(
+    _: &TransactionMatcher,
+    checkpoint: &CheckpointData,
+    matched: &MatchedTransaction,
+) -> Option<Transaction> {
+    let tx = matched.tx(checkpoint);
+    let tx_data = tx.transaction.transaction_data();
+    let total_move_calls = tx_data.move_calls().len();
+
+    // Create a structured JSON object for tx_kind
+    let kind_json = match tx_data.kind() {
+        TransactionKind::ProgrammableTransaction(pt) => {
+            serde_json::json!({
+                "type": "ProgrammableTransaction",
+                "matched_calls": matched.matched_calls,
+                "matched_events": matched.matched_events,
+                "total_move_calls": total_move_calls,
+                "inputs": pt.inputs,
+                "commands": pt.commands.iter().map(|cmd| {
+                    match cmd {
+                        Command::MoveCall(call) => {
+                            serde_json::json!({
+                                "type": "MoveCall",
+                                "package": call.package.to_string(),
+                                "module": call.module.to_string(),
+                                "function": call.function.to_string(),
+                            })
+                        },
+                        Command::TransferObjects(_, _) => serde_json::json!({"type": "TransferObjects"}),
+                        Command::SplitCoins(_, _) => serde_json::json!({"type": "SplitCoins"}),
+                        Command::MergeCoins(_, _) => serde_json::json!({"type": "MergeCoins"}),
+                        Command::Publish(_, _) => serde_json::json!({"type": "Publish"}),
+                        Command::MakeMoveVec(_, _) => serde_json::json!({"type": "MakeMoveVec"}),
+                        Command::Upgrade(_, _, _, _) => serde_json::json!({"type": "Upgrade"}),
+                    }
+                }).collect::<Vec<_>>()
+            })
+        },
+        other => serde_json::json!({
+            "type": format!("{:?}", other),
+            "matched_calls": matched.matched_calls,
+            "matched_events": matched.matched_events,
+            "total_move_calls": total_move_calls,
+        })
+    };
+
+    // Serialize the full transaction for storage
+    let serialized_tx = serde_json::to_value(&tx.transaction).unwrap_or_default();
+
+    // Extract what was actually spent on gas
+    let gas_summary = tx.effects.gas_cost_summary();
+
+    // Extract the execution status
+    let (status, error) = match tx.effects.status() {
+        ExecutionStatus::Success => ("success".to_string(), None),
+        ExecutionStatus::Failure { error, command: Some(command) } => {
+            ("failure".to_string(), Some(format!("{:?} in command {}", error, command)))
+        }
+        ExecutionStatus::Failure { error, command: None } => {
+            ("failure".to_string(), Some(format!("{:?}", error)))
+        }
+    };
+
+    Some(Transaction::new(
+        tx.transaction.digest().to_string(),
+        checkpoint.checkpoint_summary.sequence_number as i64,
+        checkpoint.checkpoint_summary.timestamp_ms as i64,
+        checkpoint.checkpoint_summary.epoch as i64,
+        tx_data.sender().to_string(),
+        kind_json,
+        tx_data.gas_budget() as i64,
+        tx_data.gas_price() as i64,
+        serialized_tx,
+        matched.matched_package.map(|p| p.to_string()),
+        (!matched.matched_objects.is_empty())
+            .then(|| matched.matched_objects.iter().map(|id| id.to_string()).collect()),
+        status,
+        error,
+        tx_data.gas_owner().to_string(),
+        gas_summary.computation_cost as i64,
+        gas_summary.storage_cost as i64,
+        gas_summary.storage_rebate as i64,
+        gas_summary.non_refundable_storage_fee as i64,
+    ))
+}
+
+// Record the digests committing to the transaction's contents
+fn checkpoint_transaction_row(
+    _: &TransactionMatcher,
+    checkpoint: &CheckpointData,
+    matched: &MatchedTransaction,
+) -> Option<models::CheckpointTransaction> {
+    let tx = matched.tx(checkpoint);
+    Some(models::CheckpointTransaction::new(
+        tx.transaction.digest().to_string(),
+        tx.effects.digest().to_string(),
+        tx.effects.events_digest().map(|d| d.to_string()),
+        checkpoint.checkpoint_summary.sequence_number as i64,
+        matched.tx_sequence_in_checkpoint as i64,
+        checkpoint.checkpoint_summary.timestamp_ms as i64,
+        checkpoint.checkpoint_summary.epoch as i64,
+    ))
+}
+
+fn effects_row(
+    _: &TransactionMatcher,
+    checkpoint: &CheckpointData,
+    matched: &MatchedTransaction,
+) -> Option<TransactionEffect> {
+    let tx = matched.tx(checkpoint);
+    Some(TransactionEffect {
+        tx_digest: tx.transaction.digest().to_string(),
+        effects_json: serde_json::to_value(&tx.effects).unwrap_or_default(),
+        created_at: None,
+        timestamp_ms: checkpoint.checkpoint_summary.timestamp_ms as i64,
+        epoch: checkpoint.checkpoint_summary.epoch as i64,
+    })
+}
+
+fn transaction_events_row(
+    _: &TransactionMatcher,
+    checkpoint: &CheckpointData,
+    matched: &MatchedTransaction,
+) -> Option<TransactionEvent> {
+    let tx = matched.tx(checkpoint);
+    tx.events.as_ref().map(|events| TransactionEvent {
+        tx_digest: tx.transaction.digest().to_string(),
+        events_json: serde_json::to_value(events).unwrap_or_default(),
+        created_at: None,
+        timestamp_ms: checkpoint.checkpoint_summary.timestamp_ms as i64,
+        epoch: checkpoint.checkpoint_summary.epoch as i64,
+    })
+}
+
+fn input_objects_row(
+    _: &TransactionMatcher,
+    checkpoint: &CheckpointData,
+    matched: &MatchedTransaction,
+) -> Option<InputObjects> {
+    let tx = matched.tx(checkpoint);
+    Some(InputObjects {
+        tx_digest: tx.transaction.digest().to_string(),
+        objects_json: serde_json::to_value(&tx.input_objects).unwrap_or_default(),
+        created_at: None,
+        timestamp_ms: checkpoint.checkpoint_summary.timestamp_ms as i64,
+        epoch: checkpoint.checkpoint_summary.epoch as i64,
+    })
+}
+
+fn output_objects_row(
+    _: &TransactionMatcher,
+    checkpoint: &CheckpointData,
+    matched: &MatchedTransaction,
+) -> Option<OutputObjects> {
+    let tx = matched.tx(checkpoint);
+    Some(OutputObjects {
+        tx_digest: tx.transaction.digest().to_string(),
+        objects_json: serde_json::to_value(&tx.output_objects).unwrap_or_default(),
+        created_at: None,
+        timestamp_ms: checkpoint.checkpoint_summary.timestamp_ms as i64,
+        epoch: checkpoint.checkpoint_summary.epoch as i64,
+    })
+}
+
+// One row per emitted event
+fn event_rows(
+    matcher: &TransactionMatcher,
+    checkpoint: &CheckpointData,
+    matched: &MatchedTransaction,
+) -> Vec<Event> {
+    let tx = matched.tx(checkpoint);
+    let Some(events) = &tx.events else {
+        return Vec::new();
+    };
+
+    let tx_digest = tx.transaction.digest().to_string();
+    events
+        .data
+        .iter()
+        .enumerate()
+        .map(|(event_seq, event)| Event {
+            tx_digest: tx_digest.clone(),
+            event_seq: event_seq as i64,
+            package_id: event.package_id.to_string(),
+            module: event.transaction_module.to_string(),
+            event_type: event.type_.to_string(),
+            sender: event.sender.to_string(),
+            contents: event.contents.clone(),
+            checkpoint_sequence_number: checkpoint.checkpoint_summary.sequence_number as i64,
+            timestamp_ms: checkpoint.checkpoint_summary.timestamp_ms as i64,
+            epoch: checkpoint.checkpoint_summary.epoch as i64,
+            created_at: None,
+            parsed_json: matcher.decoder().decode(
+                &TypeTag::Struct(Box::new(event.type_.clone())),
+                &event.contents,
+            ),
+        })
+        .collect()
+}
+
+// One decoded row per input and output object, for whichever of the two
+// fields are selected
+fn transaction_object_rows(
+    matcher: &TransactionMatcher,
+    checkpoint: &CheckpointData,
+    matched: &MatchedTransaction,
+) -> Vec<TransactionObject> {
+    let tx = matched.tx(checkpoint);
+    let tx_digest = tx.transaction.digest().to_string();
+
+    let inputs = tx.input_objects.iter()
+        .filter(|_| matcher.indexes(&IndexField::InputObjects))
+        .map(|o| ("input", o));
+    let outputs = tx.output_objects.iter()
+        .filter(|_| matcher.indexes(&IndexField::OutputObjects))
+        .map(|o| ("output", o));
+
+    inputs
+        .chain(outputs)
+        .map(|(kind, object)| {
+            let struct_tag = object.struct_tag();
+            TransactionObject {
+                tx_digest: tx_digest.clone(),
+                object_id: object.id().to_string(),
+                version: object.version().value() as i64,
+                kind: kind.to_string(),
+                object_type: struct_tag.as_ref().map(|t| t.to_string()),
+                owner_kind: owner_kind(&object.owner),
+                owner_address: object.owner.get_owner_address().ok().map(|a| a.to_string()),
+                parsed_json: struct_tag.zip(object.data.try_as_move()).and_then(|(tag, o)| {
+                    matcher.decoder().decode(&TypeTag::Struct(Box::new(tag)), o.contents())
+                }),
+                checkpoint_sequence_number: checkpoint.checkpoint_summary.sequence_number as i64,
+                timestamp_ms: checkpoint.checkpoint_summary.timestamp_ms as i64,
+                epoch: checkpoint.checkpoint_summary.epoch as i64,
+                created_at: None,
+            }
+        })
+        .collect()
+}
+
+// One row per move call command
+fn move_call_rows(
+    _: &TransactionMatcher,
+    checkpoint: &CheckpointData,
+    matched: &MatchedTransaction,
+) -> Vec<MoveCall> {
+    let tx = matched.tx(checkpoint);
+    let TransactionKind::ProgrammableTransaction(pt) = tx.transaction.transaction_data().kind() else {
+        return Vec::new();
+    };
+
+    let tx_digest = tx.transaction.digest().to_string();
+    pt.commands
+        .iter()
+        .enumerate()
+        .filter_map(|(command_index, cmd)| match cmd {
+            Command::MoveCall(call) => Some(MoveCall {
+                tx_digest: tx_digest.clone(),
+                command_index: command_index as i32,
+                package: call.package.to_string(),
+                module: call.module.to_string(),
+                function: call.function.to_string(),
+                type_arguments: serde_json::to_value(&call.type_arguments).unwrap_or_default(),
+                arguments: serde_json::to_value(&call.arguments).unwrap_or_default(),
+                checkpoint_sequence_number: checkpoint.checkpoint_summary.sequence_number as i64,
+                timestamp_ms: checkpoint.checkpoint_summary.timestamp_ms as i64,
+                epoch: checkpoint.checkpoint_summary.epoch as i64,
+                created_at: None,
+            }),
+            _ => None,
+        })
+        .collect()
+}
+
+// The version history of every object written or removed
+fn object_history_rows(
+    _: &TransactionMatcher,
+    checkpoint: &CheckpointData,
+    matched: &MatchedTransaction,
+) -> Vec<ObjectHistory> {
+    let tx = matched.tx(checkpoint);
+    let tx_digest = tx.transaction.digest().to_string();
+    let checkpoint_sequence_number = checkpoint.checkpoint_summary.sequence_number as i64;
+    let timestamp_ms = checkpoint.checkpoint_summary.timestamp_ms as i64;
+    let epoch = checkpoint.checkpoint_summary.epoch as i64;
+
+    let written = tx.output_objects.iter().map(|object| ObjectHistory {
+        object_id: object.id().to_string(),
+        version: object.version().value() as i64,
+        digest: object.digest().to_string(),
+        object_type: object.struct_tag().map(|t| t.to_string()),
+        owner_kind: Some(owner_kind(&object.owner)),
+        owner_address: object.owner.get_owner_address().ok().map(|a| a.to_string()),
+        tx_digest: tx_digest.clone(),
+        checkpoint_sequence_number,
+        timestamp_ms,
+        epoch,
+        deleted: false,
+        created_at: None,
+    });
+
+    // Wrapped objects are no longer live at the top level either
+    let removed = tx.effects.deleted().into_iter()
+        .chain(tx.effects.unwrapped_then_deleted())
+        .chain(tx.effects.wrapped())
+        .map(|(id, version, digest)| ObjectHistory {
+            object_id: id.to_string(),
+            version: version.value() as i64,
+            digest: digest.to_string(),
+            object_type: None,
+            owner_kind: None,
+            owner_address: None,
+            tx_digest: tx_digest.clone(),
+            checkpoint_sequence_number,
+            timestamp_ms,
+            epoch,
+            deleted: true,
+            created_at: None,
+        });
+
+    written.chain(removed).collect()
+}
+
+// The net balance change per owner and coin type
+fn balance_change_rows(
+    _: &TransactionMatcher,
+    checkpoint: &CheckpointData,
+    matched: &MatchedTransaction,
+) -> Vec<BalanceChange> {
+    let tx = matched.tx(checkpoint);
+    let tx_digest = tx.transaction.digest().to_string();
+    derive_balance_changes(&tx.input_objects, &tx.output_objects)
+        .into_iter()
+        .map(|((owner, coin_type), amount)| BalanceChange {
+            tx_digest: tx_digest.clone(),
+            owner,
+            coin_type,
+            amount: BigDecimal::from(amount),
+            checkpoint_sequence_number: checkpoint.checkpoint_summary.sequence_number as i64,
+            timestamp_ms: checkpoint.checkpoint_summary.timestamp_ms as i64,
+            epoch: checkpoint.checkpoint_summary.epoch as i64,
+            created_at: None,
+        })
+        .collect()
+}
+
+// Hands every checkpoint to the matcher as soon as it arrives. The other
+// pipelines wait in `process` until the checkpoints before theirs have been
+// resolved, and may all be waiting on a checkpoint that ingestion delivered
+// late. This one never waits, so that checkpoint is still resolved. It writes
+// nothing.
+pub struct CheckpointFeedPipeline(pub Arc<TransactionMatcher>);
+
+// Value of a pipeline that writes no rows
+#[derive(Debug, Clone)]
+pub struct NoRows;
+
+impl FieldCount for NoRows {
+    const FIELD_COUNT: usize = 1;
+}
+
+impl Processor for CheckpointFeedPipeline {
+    const NAME: &'static str = "checkpoint_feed";
+    type Value = NoRows;
+
+    fn process(&self, checkpoint: &Arc<CheckpointData>) -> Result<Vec<Self::Value>> {
+        self.0.feed(checkpoint);
+        Ok(vec![])
+    }
+}
+
+#[async_trait::async_trait]
+impl ConcurrentHandler for CheckpointFeedPipeline {
+    async fn commit(_values: &[Self::Value], _conn: &mut db::Connection<'_>) -> Result<usize> {
+        Ok(0)
+    }
+}
+
+// Records every processed checkpoint, matched or not, to audit coverage
+pub struct CheckpointsPipeline(pub Arc<TransactionMatcher>);
+
+impl Processor for CheckpointsPipeline {
+    const NAME: &'static str = "checkpoints";
+    type Value = Checkpoint;
+
+    fn process(&self, checkpoint: &Arc<CheckpointData>) -> Result<Vec<Self::Value>> {
+        let matched_transactions = self.0.match_checkpoint(checkpoint).transactions.len();
+
+        let summary = &checkpoint.checkpoint_summary;
+        info!("Processed checkpoint {}, found {} matching transactions",
+              summary.sequence_number, matched_transactions);
+
+        let rolling_gas = &summary.epoch_rolling_gas_cost_summary;
+        Ok(vec![Checkpoint {
+            sequence_number: summary.sequence_number as i64,
+            digest: summary.digest().to_string(),
+            epoch: summary.epoch as i64,
+            timestamp_ms: summary.timestamp_ms as i64,
+            network_total_transactions: summary.network_total_transactions as i64,
+            transaction_count: checkpoint.transactions.len() as i64,
+            matched_transactions: matched_transactions as i64,
+            computation_cost: rolling_gas.computation_cost as i64,
+            storage_cost: rolling_gas.storage_cost as i64,
+            storage_rebate: rolling_gas.storage_rebate as i64,
+            non_refundable_storage_fee: rolling_gas.non_refundable_storage_fee as i64,
+            created_at: None,
+        }])
+    }
+}
+
+#[async_trait::async_trait]
+impl ConcurrentHandler for CheckpointsPipeline {
+    async fn commit(values: &[Self::Value], conn: &mut db::Connection<'_>) -> Result<usize> {
+        let rows: Vec<&Checkpoint> = values.iter().collect();
+        Ok(insert_chunked!(conn, crate::schema::checkpoints::table, Checkpoint, rows, "checkpoints"))
+    }
+}
+
+// Records newly detected versions of tracked packages
+pub struct PackageLineagePipeline(pub Arc<TransactionMatcher>);
+
+impl Processor for PackageLineagePipeline {
+    const NAME: &'static str = "package_lineage";
+    type Value = PackageLineage;
+
+    fn process(&self, checkpoint: &Arc<CheckpointData>) -> Result<Vec<Self::Value>> {
+        Ok(self.0.match_checkpoint(checkpoint).lineage.clone())
+    }
+}
+
+#[async_trait::async_trait]
+impl ConcurrentHandler for PackageLineagePipeline {
+    async fn commit(values: &[Self::Value], conn: &mut db::Connection<'_>) -> Result<usize> {
+        let rows: Vec<&PackageLineage> = values.iter().collect();
+        Ok(insert_chunked!(conn, crate::schema::package_lineage::table, PackageLineage, rows, "package lineage"))
+    }
+}
+
+// Persists newly published packages for decoding after a restart
+pub struct PackagesPipeline(pub Arc<TransactionMatcher>);
+
+impl Processor for PackagesPipeline {
+    const NAME: &'static str = "packages";
+    type Value = Package;
+
+    fn process(&self, checkpoint: &Arc<CheckpointData>) -> Result<Vec<Self::Value>> {
+        Ok(self.0.match_checkpoint(checkpoint).packages.clone())
+    }
+}
+
+#[async_trait::async_trait]
+impl ConcurrentHandler for PackagesPipeline {
+    async fn commit(values: &[Self::Value], conn: &mut db::Connection<'_>) -> Result<usize> {
+        let rows: Vec<&Package> = values.iter().collect();
+        Ok(insert_chunked!(conn, crate::schema::packages::table, Package, rows, "packages"))
+    }
+}
+
+// Advances the live objects table to the newest version of each object. Runs
+// as a concurrent pipeline by default, or as a sequential one when `objects`
+// is selected in `PipelineConfig::sequential`.
+pub struct LiveObjectsPipeline(pub Arc<TransactionMatcher>);
+
+impl Processor for LiveObjectsPipeline {
+    const NAME: &'static str = "objects";
+    type Value = ObjectHistory;
+
+    fn process(&self, checkpoint: &Arc<CheckpointData>) -> Result<Vec<Self::Value>> {
+        let matches = self.0.match_checkpoint(checkpoint);
+        Ok(matches
+            .transactions
+            .iter()
+            .flat_map(|matched| object_history_rows(&self.0, checkpoint, matched))
+            .collect())
+    }
+}
+
+#[async_trait::async_trait]
+impl ConcurrentHandler for LiveObjectsPipeline {
+    async fn commit(values: &[Self::Value], conn: &mut db::Connection<'_>) -> Result<usize> {
+        let rows: Vec<&ObjectHistory> = values.iter().collect();
+        upsert_live_objects(conn, &rows).await
+    }
+}
+
+#[async_trait::async_trait]
+impl SequentialHandler for LiveObjectsPipeline {
+    // Newest version of each object seen so far in the batch
+    type Batch = BTreeMap<String, ObjectHistory>;
+
+    fn batch(batch: &mut Self::Batch, values: Vec<Self::Value>) {
+        for row in values {
+            match batch.get(&row.object_id) {
+                Some(existing) if existing.version >= row.version => {}
+                _ => {
+                    batch.insert(row.object_id.clone(), row);
+                }
+            }
+        }
+    }
+
+    async fn commit(batch: &Self::Batch, conn: &mut db::Connection<'_>) -> Result<usize> {
+        let rows: Vec<&ObjectHistory> = batch.values().collect();
+        upsert_live_objects(conn, &rows).await
+    }
+}
+
+// Sums balance changes into a running balance per owner and coin type. Adding
+// a change twice or out of order would corrupt the running total, so this
+// pipeline is only ever run sequentially.
+pub struct BalancesPipeline(pub Arc<TransactionMatcher>);
+
+impl Processor for BalancesPipeline {
+    const NAME: &'static str = "balances";
+    type Value = BalanceChange;
+
+    fn process(&self, checkpoint: &Arc<CheckpointData>) -> Result<Vec<Self::Value>> {
+        let matches = self.0.match_checkpoint(checkpoint);
+        Ok(matches
+            .transactions
+            .iter()
+            .flat_map(|matched| balance_change_rows(&self.0, checkpoint, matched))
+            .collect())
+    }
+}
+
+#[async_trait::async_trait]
+impl SequentialHandler for BalancesPipeline {
+    // Net change per (owner, coin type) accumulated over the batch, stamped
+    // with the latest checkpoint that touched it
+    type Batch = BTreeMap<(String, String), Balance>;
+
+    fn batch(batch: &mut Self::Batch, values: Vec<Self::Value>) {
+        for change in values {
+            let key = (change.owner.clone(), change.coin_type.clone());
+            let entry = batch.entry(key).or_insert_with(|| Balance {
+                owner: change.owner,
+                coin_type: change.coin_type,
+                balance: BigDecimal::from(0),
+                checkpoint_sequence_number: change.checkpoint_sequence_number,
+                timestamp_ms: change.timestamp_ms,
+                epoch: change.epoch,
+                created_at: None,
+            });
+
+            entry.balance += change.amount;
+            entry.checkpoint_sequence_number = change.checkpoint_sequence_number;
+            entry.timestamp_ms = change.timestamp_ms;
+            entry.epoch = change.epoch;
+        }
+    }
+
+    async fn commit(batch: &Self::Batch, conn: &mut db::Connection<'_>) -> Result<usize> {
+        use crate::schema::balances;
+        use diesel::upsert::excluded;
+
+        let rows: Vec<&Balance> = batch.values().collect();
+
+        let mut updated = 0;
+        for chunk in rows.chunks(rows_per_insert::<Balance>()) {
+            updated += diesel::insert_into(balances::table)
+                .values(chunk.to_vec())
+                .on_conflict((balances::owner, balances::coin_type))
+                .do_update()
+                .set((
+                    balances::balance.eq(balances::balance + excluded(balances::balance)),
+                    balances::checkpoint_sequence_number.eq(excluded(balances::checkpoint_sequence_number)),
+                    balances::timestamp_ms.eq(excluded(balances::timestamp_ms)),
+                    balances::epoch.eq(excluded(balances::epoch)),
+                ))
+                .execute(conn)
+                .await
+                .map_err(|e| anyhow::anyhow!("Failed to update balances: {}", e))?;
+        }
+
+        Ok(updated)
+    }
+}
+
+// Runs the custom callbacks registered for the selected fields
+pub struct CustomIndexPipeline {
+    pub callbacks: HashMap<IndexField, IndexCallback>,
+}
+
+impl Processor for CustomIndexPipeline {
+    const NAME: &'static str = "my_index_data";
+    type Value = MyIndexData;
+
+    fn process(&self, checkpoint: &Arc<CheckpointData>) -> Result<Vec<Self::Value>> {
+        let mut results = Vec::new();
+        for (field, callback) in &self.callbacks {
+            let rows = callback(checkpoint.as_ref())
+                .with_context(|| format!("Callback for {:?} failed on checkpoint {}",
+                    field, checkpoint.checkpoint_summary.sequence_number))?;
+
+            info!("Callback for {:?} returned {} rows", field, rows.len());
+            results.extend(rows);
+        }
+
+        Ok(results)
+    }
+}
+
+#[async_trait::async_trait]
+impl ConcurrentHandler for CustomIndexPipeline {
+    async fn commit(values: &[Self::Value], conn: &mut db::Connection<'_>) -> Result<usize> {
+        let rows: Vec<&MyIndexData> = values.iter().collect();
+        Ok(insert_chunked!(conn, crate::schema::my_index_data::table, MyIndexData, rows, "custom index data"))
+    }
+}
+
+// Advance the live objects table to the newest version of each object in
+// `history`. Commits may arrive out of checkpoint order, so a live row is only
+// replaced by a newer version.
+async fn upsert_live_objects(
+    conn: &mut db::Connection<'_>,
+    history: &[&ObjectHistory],
+) -> Result<usize> {
+    use crate::schema::objects;
+    use diesel::upsert::excluded;
+
+    // A single INSERT .. ON CONFLICT DO UPDATE cannot touch a row twice, so
+    // keep only the newest version of each object in the batch
+    let mut latest: BTreeMap<&str, &ObjectHistory> = BTreeMap::new();
+    for &row in history {
+        let entry = latest.entry(row.object_id.as_str()).or_insert(row);
+        if row.version > entry.version {
+            *entry = row;
+        }
+    }
+
+    let live: Vec<LiveObject> = latest.into_values().cloned().map(LiveObject::from).collect();
+
+    let mut updated = 0;
+    for chunk in live.chunks(rows_per_insert::<LiveObject>()) {
+        updated += diesel::insert_into(objects::table)
+            .values(chunk.to_vec())
+            .on_conflict(objects::object_id)
+            .do_update()
+            .set((
+                objects::version.eq(excluded(objects::version)),
+                objects::digest.eq(excluded(objects::digest)),
+                objects::object_type.eq(excluded(objects::object_type)),
+                objects::owner_kind.eq(excluded(objects::owner_kind)),
+                objects::owner_address.eq(excluded(objects::owner_address)),
+                objects::tx_digest.eq(excluded(objects::tx_digest)),
+                objects::checkpoint_sequence_number.eq(excluded(objects::checkpoint_sequence_number)),
+                objects::deleted.eq(excluded(objects::deleted)),
+                objects::timestamp_ms.eq(excluded(objects::timestamp_ms)),
+                objects::epoch.eq(excluded(objects::epoch)),
+            ))
+            .filter(objects::version.lt(excluded(objects::version)))
+            .execute(conn)
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to update live objects: {}", e))?;
+    }
+
+    Ok(updated)
+}
+
+// Net coin balance change per (owner, coin type), computed by subtracting the
+// coins a transaction consumed from the coins it produced. The gas coin is
+// both an input and an output, so gas fees are included. Only coins owned by
+// an address count: a coin wrapped in or owned by another object is not part
+// of anyone's balance. Zero changes are dropped.
+fn derive_balance_changes(inputs: &[Object], outputs: &[Object]) -> BTreeMap<(String, String), i128> {
+    let mut changes: BTreeMap<(String, String), i128> = BTreeMap::new();
+
+    let coins = |objects: &'_ [Object], sign: i128| {
+        objects
+            .iter()
+            .filter_map(move |object| {
+                let coin_type = object.coin_type_maybe()?;
+                let coin = object.as_coin_maybe()?;
+                let Owner::AddressOwner(owner) = object.owner else {
+                    return None;
+                };
+                Some(((owner.to_string(), coin_type.to_string()), sign * coin.value() as i128))
+            })
+            .collect::<Vec<_>>()
+    };
+
+    for (key, amount) in coins(inputs, -1).into_iter().chain(coins(outputs, 1)) {
+        *changes.entry(key).or_default() += amount;
+    }
+
+    changes.retain(|_, amount| *amount != 0);
+    changes
+}
+
+// Owner variant name as serialized by `Owner`, e.g. `AddressOwner`, `Shared`
+// or `Immutable`.
+fn owner_kind(owner: &Owner) -> String {
+    match serde_json::to_value(owner) {
+        Ok(serde_json::Value::Object(map)) => map.keys().next().cloned().unwrap_or_default(),
+        Ok(serde_json::Value::String(kind)) => kind,
+        _ => "Unknown".to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sui_types::base_types::{ObjectID, SuiAddress};
+    use sui_types::digests::TransactionDigest;
+    use sui_types::gas_coin::GAS;
+
+    fn coin(id: ObjectID, owner: SuiAddress, value: u64) -> Object {
+        Object::with_id_owner_gas_for_testing(id, owner, value)
+    }
+
+    fn change(owner: SuiAddress, amount: i128) -> ((String, String), i128) {
+        ((owner.to_string(), GAS::type_tag().to_string()), amount)
+    }
+
+    #[test]
+    fn gas_coin_pays_fees() {
+        let (id, sender) = (ObjectID::random(), SuiAddress::random_for_testing_only());
+
+        let changes = derive_balance_changes(&[coin(id, sender, 1000)], &[coin(id, sender, 900)]);
+        assert_eq!(changes, BTreeMap::from([change(sender, -100)]));
+    }
+
+    #[test]
+    fn merged_coins_net_to_zero() {
+        let (a, b) = (ObjectID::random(), ObjectID::random());
+        let owner = SuiAddress::random_for_testing_only();
+
+        let changes = derive_balance_changes(
+            &[coin(a, owner, 500), coin(b, owner, 300)],
+            &[coin(a, owner, 800)],
+        );
+        assert!(changes.is_empty());
+    }
+
+    #[test]
+    fn transfer_moves_balance() {
+        let id = ObjectID::random();
+        let (from, to) = (SuiAddress::random_for_testing_only(), SuiAddress::random_for_testing_only());
+
+        let changes = derive_balance_changes(&[coin(id, from, 500)], &[coin(id, to, 500)]);
+        assert_eq!(changes, BTreeMap::from([change(from, -500), change(to, 500)]));
+    }
+
+    #[test]
+    fn object_owned_coin_is_not_credited() {
+        let id = ObjectID::random();
+        let (owner, parent) = (SuiAddress::random_for_testing_only(), ObjectID::random());
+
+        let coin_object = coin(id, owner, 500).data.try_as_move().unwrap().clone();
+        let wrapped = Object::new_move(coin_object, Owner::ObjectOwner(parent.into()), TransactionDigest::genesis_marker());
+
+        let changes = derive_balance_changes(&[coin(id, owner, 500)], &[wrapped]);
+        assert_eq!(changes, BTreeMap::from([change(owner, -500)]));
+    }
+}
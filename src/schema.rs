@@ -1,16 +1,85 @@
 // @generated automatically by Diesel CLI.
 
+diesel::table! {
+    use diesel::sql_types::*;
+
+    balance_changes (tx_digest, owner, coin_type) {
+        tx_digest -> Varchar,
+        owner -> Varchar,
+        coin_type -> Varchar,
+        amount -> Numeric,
+        checkpoint_sequence_number -> Int8,
+        created_at -> Nullable<Timestamptz>,
+        timestamp_ms -> Int8,
+        epoch -> Int8,
+    }
+}
+
+diesel::table! {
+    use diesel::sql_types::*;
+
+    balances (owner, coin_type) {
+        owner -> Varchar,
+        coin_type -> Varchar,
+        balance -> Numeric,
+        checkpoint_sequence_number -> Int8,
+        timestamp_ms -> Int8,
+        epoch -> Int8,
+        created_at -> Nullable<Timestamptz>,
+    }
+}
+
 diesel::table! {
     use diesel::sql_types::*;
 
     checkpoint_transactions (tx_digest) {
         tx_digest -> Varchar,
         transaction_digest -> Varchar,
-        transaction_effects_digest -> Nullable<Varchar>,
+        transaction_effects_digest -> Varchar,
         transaction_events_digest -> Nullable<Varchar>,
-        input_objects_digest -> Nullable<Varchar>,
-        output_objects_digest -> Nullable<Varchar>,
         created_at -> Nullable<Timestamptz>,
+        timestamp_ms -> Int8,
+        epoch -> Int8,
+        checkpoint_sequence_number -> Int8,
+        tx_sequence_in_checkpoint -> Int8,
+    }
+}
+
+diesel::table! {
+    use diesel::sql_types::*;
+
+    checkpoints (sequence_number) {
+        sequence_number -> Int8,
+        digest -> Varchar,
+        epoch -> Int8,
+        timestamp_ms -> Int8,
+        network_total_transactions -> Int8,
+        transaction_count -> Int8,
+        matched_transactions -> Int8,
+        computation_cost -> Int8,
+        storage_cost -> Int8,
+        storage_rebate -> Int8,
+        non_refundable_storage_fee -> Int8,
+        created_at -> Nullable<Timestamptz>,
+    }
+}
+
+diesel::table! {
+    use diesel::sql_types::*;
+
+    events (tx_digest, event_seq) {
+        tx_digest -> Varchar,
+        event_seq -> Int8,
+        package_id -> Varchar,
+        module -> Varchar,
+        event_type -> Varchar,
+        sender -> Varchar,
+        contents -> Bytea,
+        checkpoint_sequence_number -> Int8,
+        created_at -> Nullable<Timestamptz>,
+        parsed_json -> Nullable<Jsonb>,
+        timestamp_ms -> Int8,
+        epoch -> Int8,
     }
 }
 
@@ -21,6 +90,26 @@ diesel::table! {
         tx_digest -> Varchar,
         objects_json -> Jsonb,
         created_at -> Nullable<Timestamptz>,
+        timestamp_ms -> Int8,
+        epoch -> Int8,
+    }
+}
+
+diesel::table! {
+    use diesel::sql_types::*;
+
+    move_calls (tx_digest, command_index) {
+        tx_digest -> Varchar,
+        command_index -> Int4,
+        package -> Varchar,
+        module -> Varchar,
+        function -> Varchar,
+        type_arguments -> Jsonb,
+        arguments -> Jsonb,
+        checkpoint_sequence_number -> Int8,
+        created_at -> Nullable<Timestamptz>,
+        timestamp_ms -> Int8,
+        epoch -> Int8,
     }
 }
 
@@ -33,6 +122,44 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    use diesel::sql_types::*;
+
+    objects (object_id) {
+        object_id -> Varchar,
+        version -> Int8,
+        digest -> Varchar,
+        object_type -> Nullable<Varchar>,
+        owner_kind -> Nullable<Varchar>,
+        owner_address -> Nullable<Varchar>,
+        tx_digest -> Varchar,
+        checkpoint_sequence_number -> Int8,
+        deleted -> Bool,
+        created_at -> Nullable<Timestamptz>,
+        timestamp_ms -> Int8,
+        epoch -> Int8,
+    }
+}
+
+diesel::table! {
+    use diesel::sql_types::*;
+
+    objects_history (object_id, version) {
+        object_id -> Varchar,
+        version -> Int8,
+        digest -> Varchar,
+        object_type -> Nullable<Varchar>,
+        owner_kind -> Nullable<Varchar>,
+        owner_address -> Nullable<Varchar>,
+        tx_digest -> Varchar,
+        checkpoint_sequence_number -> Int8,
+        deleted -> Bool,
+        created_at -> Nullable<Timestamptz>,
+        timestamp_ms -> Int8,
+        epoch -> Int8,
+    }
+}
+
 diesel::table! {
     use diesel::sql_types::*;
 
@@ -40,6 +167,35 @@ diesel::table! {
         tx_digest -> Varchar,
         objects_json -> Jsonb,
         created_at -> Nullable<Timestamptz>,
+        timestamp_ms -> Int8,
+        epoch -> Int8,
+    }
+}
+
+diesel::table! {
+    use diesel::sql_types::*;
+
+    package_lineage (package_id) {
+        package_id -> Varchar,
+        original_package_id -> Varchar,
+        previous_package_id -> Varchar,
+        version -> Int8,
+        tx_digest -> Varchar,
+        checkpoint_sequence_number -> Int8,
+        created_at -> Nullable<Timestamptz>,
+    }
+}
+
+diesel::table! {
+    use diesel::sql_types::*;
+
+    packages (package_id) {
+        package_id -> Varchar,
+        original_package_id -> Varchar,
+        version -> Int8,
+        move_package -> Bytea,
+        checkpoint_sequence_number -> Int8,
+        created_at -> Nullable<Timestamptz>,
     }
 }
 
@@ -50,6 +206,8 @@ diesel::table! {
         tx_digest -> Varchar,
         effects_json -> Jsonb,
         created_at -> Nullable<Timestamptz>,
+        timestamp_ms -> Int8,
+        epoch -> Int8,
     }
 }
 
@@ -60,6 +218,27 @@ diesel::table! {
         tx_digest -> Varchar,
         events_json -> Jsonb,
         created_at -> Nullable<Timestamptz>,
+        timestamp_ms -> Int8,
+        epoch -> Int8,
+    }
+}
+
+diesel::table! {
+    use diesel::sql_types::*;
+
+    transaction_objects (tx_digest, object_id, version) {
+        tx_digest -> Varchar,
+        object_id -> Varchar,
+        version -> Int8,
+        kind -> Varchar,
+        object_type -> Nullable<Varchar>,
+        owner_kind -> Varchar,
+        owner_address -> Nullable<Varchar>,
+        parsed_json -> Nullable<Jsonb>,
+        checkpoint_sequence_number -> Int8,
+        created_at -> Nullable<Timestamptz>,
+        timestamp_ms -> Int8,
+        epoch -> Int8,
     }
 }
 
@@ -75,20 +254,46 @@ diesel::table! {
         gas_price -> Int8,
         serialized_tx -> Jsonb,
         created_at -> Nullable<Timestamptz>,
+        matched_package -> Nullable<Varchar>,
+        matched_object_ids -> Nullable<Array<Text>>,
+        status -> Varchar,
+        error -> Nullable<Varchar>,
+        gas_owner -> Varchar,
+        computation_cost -> Int8,
+        storage_cost -> Int8,
+        storage_rebate -> Int8,
+        non_refundable_storage_fee -> Int8,
+        timestamp_ms -> Int8,
+        epoch -> Int8,
     }
 }
 
+diesel::joinable!(balance_changes -> transactions (tx_digest));
+diesel::joinable!(checkpoint_transactions -> transactions (tx_digest));
+diesel::joinable!(events -> transactions (tx_digest));
 diesel::joinable!(input_objects -> transactions (tx_digest));
+diesel::joinable!(move_calls -> transactions (tx_digest));
 diesel::joinable!(output_objects -> transactions (tx_digest));
 diesel::joinable!(transaction_effects -> transactions (tx_digest));
 diesel::joinable!(transaction_events -> transactions (tx_digest));
+diesel::joinable!(transaction_objects -> transactions (tx_digest));
 
 diesel::allow_tables_to_appear_in_same_query!(
+    balance_changes,
+    balances,
     checkpoint_transactions,
+    checkpoints,
+    events,
     input_objects,
+    move_calls,
     my_index_data,
+    objects,
+    objects_history,
     output_objects,
+    package_lineage,
+    packages,
     transaction_effects,
     transaction_events,
+    transaction_objects,
     transactions,
 );